@@ -0,0 +1,1006 @@
+//! The guessing game from chapter 2, grown a few options.
+
+use common::color::{paint, Color, ColorChoice};
+use common::config::{GuessDefaults, FILE_NAME};
+use common::messages::Lang;
+use common::InputError;
+use log::debug;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::env;
+use std::error;
+use std::fmt::{self, Display};
+use std::fs::{self, File};
+use std::io;
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How the game is played, as set by the command line and `rust-book.toml`.
+pub struct Config {
+    min: u32,
+    max: u32,
+    max_guesses: Option<u32>,
+    seed: Option<u64>,
+    hints: bool,
+    color: ColorChoice,
+    auto: bool,
+    two_player: bool,
+    timer: bool,
+    lang: Lang,
+    /// Count a repeated guess as an attempt instead of only warning.
+    allow_repeats: bool,
+    /// Read the whole session from this file instead of stdin.
+    from_file: Option<PathBuf>,
+}
+
+impl Config {
+    /// Reads the command-line `args` (without the program name) on top of
+    /// `defaults`, such as the ones `common::config::load` finds in
+    /// `rust-book.toml`.
+    pub fn from_args(
+        args: impl Iterator<Item = String>,
+        defaults: &GuessDefaults,
+    ) -> Result<Config, Error> {
+        Config::build(args, defaults).map_err(Error::Usage)
+    }
+
+    /// Starts from the built-in game, applies the `[guess]` table on top, and
+    /// then the flags in `args`.
+    fn build(
+        mut args: impl Iterator<Item = String>,
+        defaults: &GuessDefaults,
+    ) -> Result<Config, String> {
+        let mut config = Config {
+            min: 1,
+            max: 100,
+            max_guesses: None,
+            seed: None,
+            hints: false,
+            color: ColorChoice::Auto,
+            auto: false,
+            two_player: false,
+            timer: true,
+            lang: Lang::default(),
+            allow_repeats: false,
+            from_file: None,
+        };
+
+        // The config file is applied the same way as the flags below: its
+        // difficulty first, then any range it gives explicitly.
+        if let Some(difficulty) = &defaults.difficulty {
+            let difficulty: Difficulty = parse_default("difficulty", difficulty)?;
+            difficulty.preset().apply(&mut config);
+        }
+        config.min = defaults.min.unwrap_or(config.min);
+        config.max = defaults.max.unwrap_or(config.max);
+        if defaults.max_guesses == Some(0) {
+            return Err(format!("max-guesses in {FILE_NAME} must be at least 1"));
+        }
+        config.max_guesses = defaults.max_guesses.or(config.max_guesses);
+        if let Some(lang) = &defaults.lang {
+            config.lang = parse_default("lang", lang)?;
+        }
+
+        // Explicit range flags win over --difficulty wherever they appear, so
+        // they are held back until the preset has been applied.
+        let mut difficulty = None;
+        let (mut min, mut max, mut max_guesses) = (None, None, None);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--min" => min = Some(parse_flag_value("--min", args.next())?),
+                "--max" => max = Some(parse_flag_value("--max", args.next())?),
+                "--max-guesses" => {
+                    let limit = parse_flag_value("--max-guesses", args.next())?;
+                    if limit == 0 {
+                        return Err("--max-guesses must be at least 1".to_string());
+                    }
+                    max_guesses = Some(limit);
+                }
+                "--difficulty" => {
+                    difficulty = Some(parse_flag_value::<Difficulty>("--difficulty", args.next())?)
+                }
+                "--seed" => config.seed = Some(parse_flag_value("--seed", args.next())?),
+                "--hints" => config.hints = true,
+                "--color" => config.color = parse_flag_value("--color", args.next())?,
+                "--auto" => config.auto = true,
+                "--two-player" => config.two_player = true,
+                "--no-timer" => config.timer = false,
+                "--allow-repeats" => config.allow_repeats = true,
+                "--from-file" => {
+                    config.from_file = Some(parse_flag_value("--from-file", args.next())?)
+                }
+                "--lang" => config.lang = parse_flag_value("--lang", args.next())?,
+                _ => return Err(format!("unknown argument '{arg}'")),
+            }
+        }
+
+        // Like the range flags, --seed beats GUESS_SEED, which is only looked
+        // at (and checked) without it.
+        if config.seed.is_none() {
+            config.seed = env::var("GUESS_SEED")
+                .ok()
+                .map(|seed| parse_flag_value("GUESS_SEED", Some(seed)))
+                .transpose()?;
+        }
+
+        if let Some(difficulty) = difficulty {
+            difficulty.preset().apply(&mut config);
+        }
+        config.min = min.unwrap_or(config.min);
+        config.max = max.unwrap_or(config.max);
+        config.max_guesses = max_guesses.or(config.max_guesses);
+
+        if config.min >= config.max {
+            return Err(format!(
+                "--min ({}) must be less than --max ({})",
+                config.min, config.max
+            ));
+        }
+
+        if config.auto && config.two_player {
+            return Err("--auto and --two-player cannot be combined".to_string());
+        }
+
+        if config.from_file.is_some() && (config.auto || config.two_player) {
+            return Err("--from-file cannot be combined with --auto or --two-player".to_string());
+        }
+
+        Ok(config)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// The range and guess limit a difficulty stands for.
+struct Preset {
+    min: u32,
+    max: u32,
+    max_guesses: Option<u32>,
+}
+
+impl Preset {
+    fn apply(self, config: &mut Config) {
+        config.min = self.min;
+        config.max = self.max;
+        config.max_guesses = self.max_guesses;
+    }
+}
+
+impl Difficulty {
+    fn preset(self) -> Preset {
+        match self {
+            Difficulty::Easy => Preset {
+                min: 1,
+                max: 20,
+                max_guesses: None,
+            },
+            Difficulty::Medium => Preset {
+                min: 1,
+                max: 100,
+                max_guesses: Some(10),
+            },
+            Difficulty::Hard => Preset {
+                min: 1,
+                max: 1000,
+                max_guesses: Some(12),
+            },
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(()),
+        }
+    }
+}
+
+fn parse_flag_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or(format!("{flag} needs a value"))?;
+    value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a valid value for {flag}"))
+}
+
+fn parse_default<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a valid value for {key} in {FILE_NAME}"))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GuessResult {
+    TooLow,
+    TooHigh,
+    Correct,
+}
+
+/// How a round ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameOutcome {
+    Won {
+        attempts: u32,
+    },
+    Lost,
+    /// The player asked to see the secret. Counts as a loss.
+    GaveUp,
+    Quit,
+}
+
+impl GuessResult {
+    fn label(self) -> &'static str {
+        match self {
+            GuessResult::TooLow => "too small",
+            GuessResult::TooHigh => "too big",
+            GuessResult::Correct => "correct",
+        }
+    }
+}
+
+fn evaluate_guess(guess: u32, secret: u32) -> GuessResult {
+    match guess.cmp(&secret) {
+        Ordering::Less => GuessResult::TooLow,
+        Ordering::Greater => GuessResult::TooHigh,
+        Ordering::Equal => GuessResult::Correct,
+    }
+}
+
+/// Where guesses come from: a person typing lines, or the built-in solver.
+trait Guesser {
+    /// Returns the next line of input, or `None` once there is no more.
+    fn next_line(&mut self) -> io::Result<Option<String>>;
+
+    /// Called with the result of every guess that was compared to the secret.
+    fn feedback(&mut self, _guess: u32, _result: GuessResult) {}
+
+    /// Whether the game should echo each line, since nobody typed it.
+    fn echo(&self) -> bool {
+        false
+    }
+}
+
+impl<R: BufRead> Guesser for R {
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        common::read_line(self)
+    }
+}
+
+/// Guesses replayed from a file, echoed so the transcript reads as if they
+/// had been typed.
+struct Replay<R>(R);
+
+impl<R: BufRead> Guesser for Replay<R> {
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        common::read_line(&mut self.0)
+    }
+
+    fn echo(&self) -> bool {
+        true
+    }
+}
+
+/// Plays optimally by halving the remaining interval on every guess.
+struct Solver {
+    low: u32,
+    high: u32,
+}
+
+impl Solver {
+    fn new(config: &Config) -> Solver {
+        Solver {
+            low: config.min,
+            high: config.max,
+        }
+    }
+}
+
+impl Guesser for Solver {
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        if self.low > self.high {
+            return Ok(None);
+        }
+        Ok(Some((self.low + (self.high - self.low) / 2).to_string()))
+    }
+
+    fn feedback(&mut self, guess: u32, result: GuessResult) {
+        match result {
+            GuessResult::TooLow => self.low = guess + 1,
+            GuessResult::TooHigh => self.high = guess - 1,
+            GuessResult::Correct => (),
+        }
+        debug!("solver narrowed the range to {}..={}", self.low, self.high);
+    }
+
+    fn echo(&self) -> bool {
+        true
+    }
+}
+
+/// The most guesses binary search can need for the configured range:
+/// floor(log2(n)) + 1 for n possible numbers, i.e. the bit length of n.
+fn optimal_guesses(config: &Config) -> u32 {
+    let size = u64::from(config.max - config.min) + 1;
+    u64::BITS - size.leading_zeros()
+}
+
+fn run_game<G: Guesser, W: Write>(
+    mut guesser: G,
+    mut output: W,
+    config: &Config,
+    secret: u32,
+) -> io::Result<GameOutcome> {
+    debug!("the secret is {secret}");
+    let _in_play = SecretInPlay::new(secret);
+    let use_color = config.color.enabled();
+    writeln!(output, "Guess the number ({}–{})!", config.min, config.max)?;
+
+    let mut attempts: u32 = 0;
+    let mut last_distance: Option<u32> = None;
+    // Every guess that counted this round, for the "history" command.
+    let mut history: Vec<(u32, GuessResult)> = Vec::new();
+    // Where the secret can still be, for the "hint" command.
+    let (mut low, mut high) = (config.min, config.max);
+    let started = Instant::now();
+    loop {
+        if config.max_guesses == Some(attempts) {
+            writeln!(output, "Out of guesses! The number was {secret}.")?;
+            return Ok(GameOutcome::Lost);
+        }
+
+        let left = config.max_guesses.map(|limit| limit - attempts);
+        write!(output, "{}", config.lang.guess_prompt(left))?;
+        output.flush()?;
+        let guess = match guesser.next_line()? {
+            Some(line) => line,
+            None => {
+                writeln!(output)?;
+                writeln!(output, "No more input, goodbye!")?;
+                return Ok(GameOutcome::Quit);
+            }
+        };
+        if guesser.echo() {
+            writeln!(output, "{guess}")?;
+        }
+
+        match guess.to_lowercase().as_str() {
+            "q" | "quit" => {
+                writeln!(output, "Thanks for playing!")?;
+                return Ok(GameOutcome::Quit);
+            }
+            "reveal" | "giveup" => {
+                writeln!(output, "The number was {secret}.")?;
+                return Ok(GameOutcome::GaveUp);
+            }
+            "h" | "history" => {
+                write_history(&mut output, &history)?;
+                continue;
+            }
+            "hint" => {
+                writeln!(output, "The number is between {low} and {high}.")?;
+                continue;
+            }
+            _ => (),
+        }
+
+        let guess: u32 = match common::parse_input(&guess) {
+            Ok(num) => num,
+            Err(InputError::NotANumber)
+                if guess.trim().starts_with('-')
+                    && common::parse_number::<i128>(&guess).is_some() =>
+            {
+                writeln!(output, "Guesses can't be negative.")?;
+                continue;
+            }
+            // Too big for a u32, so past any range: answer as for the largest.
+            Err(InputError::NotANumber) if common::parse_number::<u128>(&guess).is_some() => {
+                writeln!(output, "{}", out_of_range(u32::MAX, config))?;
+                continue;
+            }
+            Err(err) => {
+                writeln!(output, "{err}")?;
+                continue;
+            }
+        };
+
+        // Both ends of the range are fair guesses. Anything past them doesn't
+        // count as an attempt.
+        if !(config.min..=config.max).contains(&guess) {
+            writeln!(output, "{}", out_of_range(guess, config))?;
+            continue;
+        }
+
+        if history.iter().any(|&(earlier, _)| earlier == guess) {
+            writeln!(output, "You already guessed that.")?;
+            if !config.allow_repeats {
+                continue;
+            }
+        }
+
+        attempts += 1;
+        let distance = guess.abs_diff(secret);
+        let hint = match last_distance {
+            Some(last) if config.hints && distance < last => " (warmer)",
+            Some(last) if config.hints && distance > last => " (colder)",
+            _ => "",
+        };
+        last_distance = Some(distance);
+
+        let result = evaluate_guess(guess, secret);
+        debug!("attempt {attempts}: {guess} is {result:?}");
+        guesser.feedback(guess, result);
+        history.push((guess, result));
+        match result {
+            GuessResult::TooLow => low = low.max(guess + 1),
+            GuessResult::TooHigh => high = high.min(guess - 1),
+            GuessResult::Correct => (),
+        }
+        match result {
+            GuessResult::TooLow => writeln!(
+                output,
+                "{}{hint}",
+                paint("Too small!", Color::Blue, use_color)
+            )?,
+            GuessResult::TooHigh => {
+                writeln!(output, "{}{hint}", paint("Too big!", Color::Red, use_color))?
+            }
+            GuessResult::Correct => {
+                let noun = plural(attempts, "guess", "guesses");
+                let message = format!("{guess} is correct: you got it in {attempts} {noun}!");
+                writeln!(output, "{}", paint(&message, Color::Green, use_color))?;
+                writeln!(
+                    output,
+                    "Score: {}",
+                    score(attempts, optimal_guesses(config))
+                )?;
+                if config.timer {
+                    writeln!(output, "Solved in {}.", format_elapsed(started.elapsed()))?;
+                }
+                return Ok(GameOutcome::Won { attempts });
+            }
+        }
+        // The prompt itself says when only one guess is left.
+        if let Some(left @ 2..=3) = config.max_guesses.map(|limit| limit - attempts) {
+            writeln!(output, "{}", config.lang.few_guesses_left(left))?;
+        }
+    }
+}
+
+/// Explains why `guess` can't be right. A guess more than a whole range
+/// away from the nearest bound gets a blunter nudge than one just past it.
+fn out_of_range(guess: u32, config: &Config) -> String {
+    // In u64, since the range can cover every u32.
+    let (guess, min, max) = (
+        u64::from(guess),
+        u64::from(config.min),
+        u64::from(config.max),
+    );
+    let size = max - min + 1;
+    if guess > max + size {
+        format!("Way too high — the number is at most {}.", config.max)
+    } else if guess < min.saturating_sub(size) {
+        format!("Way too low — the number is at least {}.", config.min)
+    } else {
+        format!(
+            "That's outside the range ({}–{}), try again.",
+            config.min, config.max
+        )
+    }
+}
+
+/// Compares a win against the `optimal` number of guesses, e.g. "optimal!"
+/// or "+5 over optimal". Winning in fewer is possible with a lucky guess.
+fn score(attempts: u32, optimal: u32) -> String {
+    match attempts.cmp(&optimal) {
+        Ordering::Less => format!("{} under optimal, lucky!", optimal - attempts),
+        Ordering::Equal => "optimal!".to_string(),
+        Ordering::Greater => format!("+{} over optimal", attempts - optimal),
+    }
+}
+
+/// Prints the round's guesses so far, e.g. "50 → too big, 25 → too small".
+fn write_history(output: &mut impl Write, history: &[(u32, GuessResult)]) -> io::Result<()> {
+    if history.is_empty() {
+        return writeln!(output, "No guesses yet.");
+    }
+    let entries: Vec<String> = history
+        .iter()
+        .map(|(guess, result)| format!("{guess} → {}", result.label()))
+        .collect();
+    writeln!(output, "{}", entries.join(", "))
+}
+
+/// Formats short games as "12.4s" and longer ones as "1m03s".
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    // Anything that would round up to "60.0s" is a minute.
+    if secs < 59.95 {
+        format!("{secs:.1}s")
+    } else {
+        let secs = secs.round() as u64;
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+/// Best (fewest) guesses per range, keyed by strings like "1..=100".
+type Scores = BTreeMap<String, u32>;
+
+fn scores_path() -> PathBuf {
+    match common::config::config_dir() {
+        Some(dir) => dir.join("rust-guess").join("scores.json"),
+        None => PathBuf::from("rust-guess-scores.json"),
+    }
+}
+
+/// A missing or corrupt scores file is treated as an empty leaderboard.
+fn load_scores(path: &Path) -> Scores {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_scores(path: &Path, scores: &Scores) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(scores)?)
+}
+
+/// Returns true if `attempts` beats the stored record for this range.
+fn record_score(config: &Config, attempts: u32) -> io::Result<bool> {
+    let path = scores_path();
+    debug!("recording {attempts} in {}", path.display());
+    let mut scores = load_scores(&path);
+    let key = format!("{}..={}", config.min, config.max);
+
+    if scores.get(&key).is_some_and(|&best| best <= attempts) {
+        return Ok(false);
+    }
+    scores.insert(key, attempts);
+    save_scores(&path, &scores)?;
+    Ok(true)
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The command line could not be understood; the message says why.
+    Usage(String),
+    Io(io::Error),
+    /// The player ran out of guesses or gave up. The game has already said
+    /// so, so this only sets the exit status.
+    Lost,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(msg) => write!(f, "{msg}"),
+            Error::Io(err) => write!(f, "I/O error: {err}"),
+            Error::Lost => write!(f, "out of guesses"),
+        }
+    }
+}
+
+impl Error {
+    /// The status `guess` exits with: 1 for a command line or I/O problem, 3
+    /// for a lost game. (0 is a win or a quit.)
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Usage(_) | Error::Io(_) => 1,
+            Error::Lost => 3,
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// The secret of the round in progress, for the Ctrl-C handler to reveal.
+static CURRENT_SECRET: Mutex<Option<u32>> = Mutex::new(None);
+
+/// Marks `secret` as in play until the round it belongs to ends.
+struct SecretInPlay;
+
+impl SecretInPlay {
+    fn new(secret: u32) -> Self {
+        *CURRENT_SECRET.lock().unwrap() = Some(secret);
+        SecretInPlay
+    }
+}
+
+impl Drop for SecretInPlay {
+    fn drop(&mut self) {
+        *CURRENT_SECRET.lock().unwrap() = None;
+    }
+}
+
+/// The terminal settings from before a secret is read with echo off. Exiting
+/// on Ctrl-C skips rpassword's own cleanup, so the handler puts them back.
+#[cfg(unix)]
+static SAVED_TERMINAL: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+/// Reads a line from the terminal without echoing it.
+fn read_hidden() -> io::Result<String> {
+    #[cfg(unix)]
+    {
+        // SAFETY: tcgetattr only writes to the termios it is given, and that
+        // is only read if the call succeeded.
+        let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } == 0 {
+            *SAVED_TERMINAL.lock().unwrap() = Some(termios);
+        }
+    }
+    let answer = rpassword::read_password();
+    #[cfg(unix)]
+    {
+        *SAVED_TERMINAL.lock().unwrap() = None;
+    }
+    answer
+}
+
+/// Puts back the settings `read_hidden` saved, if it is in the middle of
+/// reading.
+fn restore_terminal() {
+    #[cfg(unix)]
+    if let Some(termios) = *SAVED_TERMINAL.lock().unwrap() {
+        // SAFETY: `termios` came from tcgetattr on the same descriptor.
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+    }
+}
+
+/// What `guess` exits with when interrupted: 128 plus SIGINT, as shells do.
+const INTERRUPTED: i32 = 130;
+
+/// Makes Ctrl-C say goodbye, and what the number was, instead of just
+/// killing the game.
+fn handle_interrupts() {
+    let installed = ctrlc::set_handler(|| {
+        restore_terminal();
+        match *CURRENT_SECRET.lock().unwrap() {
+            Some(secret) => println!("\nInterrupted — the number was {secret}. Bye!"),
+            None => println!("\nInterrupted. Bye!"),
+        }
+        process::exit(INTERRUPTED);
+    });
+    // Without a handler Ctrl-C still works, just less politely.
+    if let Err(err) = installed {
+        debug!("could not install the Ctrl-C handler: {err}");
+    }
+}
+
+/// Plays one game configured by the command-line `args` (without the program
+/// name) on stdin and stdout.
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let defaults = common::config::load().map_err(|err| Error::Usage(err.to_string()))?;
+    let config = Config::from_args(args, &defaults.guess)?;
+    handle_interrupts();
+    run_with(&config)
+}
+
+fn run_with(config: &Config) -> Result<(), Error> {
+    let mut rng = match config.seed {
+        Some(seed) => {
+            debug!("seeding the RNG with {seed}");
+            StdRng::seed_from_u64(seed)
+        }
+        None => {
+            debug!("seeding the RNG from entropy");
+            StdRng::from_entropy()
+        }
+    };
+
+    if config.auto {
+        let secret_number = rng.gen_range(config.min..=config.max);
+        return match run_game(Solver::new(config), io::stdout(), config, secret_number)? {
+            GameOutcome::Won { attempts } => {
+                let optimal = optimal_guesses(config);
+                assert!(
+                    attempts <= optimal,
+                    "solver took {attempts} steps but should need at most {optimal}"
+                );
+                println!("The solver needed {attempts} of at most {optimal} steps.");
+                Ok(())
+            }
+            GameOutcome::Lost | GameOutcome::GaveUp => Err(Error::Lost),
+            GameOutcome::Quit => Ok(()),
+        };
+    }
+
+    match &config.from_file {
+        Some(path) => {
+            let file = File::open(path)
+                .map_err(|err| Error::Usage(format!("cannot read '{}': {err}", path.display())))?;
+            debug!("replaying input from {}", path.display());
+            play_session(BufReader::new(file), true, config, &mut rng)
+        }
+        None => play_session(io::stdin().lock(), false, config, &mut rng),
+    }
+}
+
+/// Plays one round against a secret drawn from `rng`, reading guesses from
+/// `input` and writing everything the player would see to `output`.
+pub fn play(
+    config: &Config,
+    rng: &mut impl Rng,
+    input: impl BufRead,
+    output: impl Write,
+) -> io::Result<GameOutcome> {
+    let secret_number = rng.gen_range(config.min..=config.max);
+    run_game(input, output, config, secret_number)
+}
+
+/// Plays rounds until the player stops. When `replay` is set, the input
+/// comes from a file and everything read from it is echoed.
+fn play_session(
+    mut input: impl BufRead,
+    replay: bool,
+    config: &Config,
+    rng: &mut StdRng,
+) -> Result<(), Error> {
+    let mut output = io::stdout();
+    let mut stats = SessionStats::default();
+    loop {
+        // Every round gets a fresh secret and starts its own attempt count.
+        let outcome = if config.two_player {
+            let Some(secret_number) = read_secret(&mut input, &mut output, config)? else {
+                writeln!(output)?;
+                stats.write_summary(&mut output)?;
+                return Ok(());
+            };
+            run_game(&mut input, &mut output, config, secret_number)?
+        } else if replay {
+            let secret_number = rng.gen_range(config.min..=config.max);
+            run_game(Replay(&mut input), &mut output, config, secret_number)?
+        } else {
+            play(config, rng, &mut input, &mut output)?
+        };
+        if let GameOutcome::Quit = outcome {
+            stats.write_summary(&mut output)?;
+            return Ok(());
+        }
+        stats.record(&outcome);
+
+        if let GameOutcome::Won { attempts } = outcome {
+            match record_score(config, attempts) {
+                Ok(true) => writeln!(output, "New record!")?,
+                Ok(false) => (),
+                Err(err) => eprintln!("guess: could not save scores: {err}"),
+            }
+        }
+
+        let answer = common::prompt_from(&mut input, &mut output, config.lang.play_again_prompt())?;
+        if let (true, Some(answer)) = (replay, &answer) {
+            writeln!(output, "{answer}")?;
+        }
+        match answer {
+            Some(answer) if config.lang.is_yes(&answer) => continue,
+            Some(_) => writeln!(output, "Thanks for playing!")?,
+            None => writeln!(output)?,
+        }
+        stats.write_summary(&mut output)?;
+        // The exit status reports how the last round went.
+        return match outcome {
+            GameOutcome::Lost | GameOutcome::GaveUp => Err(Error::Lost),
+            _ => Ok(()),
+        };
+    }
+}
+
+/// Asks the first player for the secret, re-prompting until it is inside the
+/// range. On a terminal the digits are not echoed, so the second player can't
+/// see them. Returns `None` at end of input.
+fn read_secret(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    config: &Config,
+) -> io::Result<Option<u32>> {
+    let prompt = config.lang.secret_prompt(config.min, config.max);
+    loop {
+        let answer = if io::stdin().is_terminal() {
+            write!(output, "{prompt}")?;
+            output.flush()?;
+            read_hidden()?
+        } else {
+            match common::prompt_from(input, output, &prompt)? {
+                Some(answer) => answer,
+                None => return Ok(None),
+            }
+        };
+
+        match common::parse_input(&answer) {
+            Ok(secret) if (config.min..=config.max).contains(&secret) => return Ok(Some(secret)),
+            Err(InputError::Empty) => writeln!(output, "{}", InputError::Empty)?,
+            _ => writeln!(
+                output,
+                "The secret must be a whole number from {} to {}.",
+                config.min, config.max
+            )?,
+        }
+    }
+}
+
+/// Totals across every finished round of one session.
+#[derive(Default)]
+struct SessionStats {
+    played: u32,
+    won: u32,
+    gave_up: u32,
+    /// Fewest guesses in a won round; losses never count.
+    best: Option<u32>,
+    guesses_in_wins: u32,
+    /// How many rounds were won in each number of guesses.
+    wins_by_attempts: BTreeMap<u32, u32>,
+}
+
+impl SessionStats {
+    fn record(&mut self, outcome: &GameOutcome) {
+        self.played += 1;
+        match *outcome {
+            GameOutcome::Won { attempts } => {
+                self.won += 1;
+                self.guesses_in_wins += attempts;
+                self.best = Some(self.best.map_or(attempts, |best| best.min(attempts)));
+                *self.wins_by_attempts.entry(attempts).or_default() += 1;
+            }
+            GameOutcome::GaveUp => self.gave_up += 1,
+            GameOutcome::Lost | GameOutcome::Quit => (),
+        }
+    }
+
+    fn write_summary(&self, output: &mut impl Write) -> io::Result<()> {
+        writeln!(output, "Session summary:")?;
+        writeln!(output, "  Games played: {}", self.played)?;
+        writeln!(output, "  Games won:    {}", self.won)?;
+        writeln!(output, "  Gave up:      {}", self.gave_up)?;
+        match self.best {
+            Some(best) => writeln!(
+                output,
+                "  Best game:    {best} {}",
+                plural(best, "guess", "guesses")
+            )?,
+            None => writeln!(output, "  Best game:    n/a")?,
+        }
+        if self.won > 0 {
+            let average = f64::from(self.guesses_in_wins) / f64::from(self.won);
+            writeln!(output, "  Average:      {average:.1} guesses per win")?;
+        } else {
+            writeln!(output, "  Average:      n/a")?;
+        }
+        write_histogram(output, &self.wins_by_attempts)
+    }
+}
+
+/// Draws a bar for each pair of guess counts, from the quickest win to the
+/// slowest, e.g. "3-4 guesses: ███ (3)".
+fn write_histogram(output: &mut impl Write, wins: &BTreeMap<u32, u32>) -> io::Result<()> {
+    let (Some(&quickest), Some(&slowest)) = (wins.keys().next(), wins.keys().next_back()) else {
+        return writeln!(output, "  Histogram:    no games won");
+    };
+    writeln!(output, "  Histogram:")?;
+    // Wins take at least one guess, so the buckets are 1-2, 3-4, and so on.
+    for bucket in (quickest - 1) / 2..=(slowest - 1) / 2 {
+        let (low, high) = (2 * bucket + 1, 2 * bucket + 2);
+        let count: u32 = wins.range(low..=high).map(|(_, count)| count).sum();
+        let bar = "█".repeat(count as usize);
+        let bar = if count > 0 { bar + " " } else { bar };
+        writeln!(output, "    {low}-{high} guesses: {bar}({count})")?;
+    }
+    Ok(())
+}
+
+fn plural<'a>(count: u32, singular: &'a str, plural: &'a str) -> &'a str {
+    if count == 1 {
+        singular
+    } else {
+        plural
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(args: &[&str]) -> Config {
+        let args = ["--color", "never", "--no-timer"].iter().chain(args);
+        Config::build(args.map(|arg| arg.to_string()), &GuessDefaults::default()).unwrap()
+    }
+
+    /// Plays a round against `secret` with `input` typed in, and returns how
+    /// it ended along with what was printed.
+    fn play_against(config: &Config, secret: u32, input: &str) -> (GameOutcome, String) {
+        let mut output = Vec::new();
+        let outcome = run_game(input.as_bytes(), &mut output, config, secret).unwrap();
+        (outcome, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn evaluate_guess_compares_with_the_secret() {
+        assert_eq!(evaluate_guess(10, 20), GuessResult::TooLow);
+        assert_eq!(evaluate_guess(30, 20), GuessResult::TooHigh);
+        assert_eq!(evaluate_guess(20, 20), GuessResult::Correct);
+    }
+
+    #[test]
+    fn first_guess_can_win() {
+        let (outcome, output) = play_against(&config(&[]), 42, "42\n");
+        assert_eq!(outcome, GameOutcome::Won { attempts: 1 });
+        assert!(output.contains("42 is correct: you got it in 1 guess!"));
+    }
+
+    #[test]
+    fn too_small_and_too_big_until_correct() {
+        let (outcome, output) = play_against(&config(&[]), 42, "10\n90\n50\n42\n");
+        assert_eq!(outcome, GameOutcome::Won { attempts: 4 });
+        let feedback: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("Please input your guess: "))
+            .collect();
+        assert_eq!(feedback[..3], ["Too small!", "Too big!", "Too big!"]);
+    }
+
+    #[test]
+    fn hints_compare_with_the_previous_guess() {
+        // 40 away, then 20 (closer), 30 (further), and 30 again (neither).
+        let input = "10\n30\n20\n80\n50\n";
+        let feedback = |config: &Config| {
+            let (outcome, output) = play_against(config, 50, input);
+            assert_eq!(outcome, GameOutcome::Won { attempts: 5 });
+            output
+                .lines()
+                .filter_map(|line| line.strip_prefix("Please input your guess: "))
+                .take(4)
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            feedback(&config(&["--hints"])),
+            [
+                "Too small!",
+                "Too small! (warmer)",
+                "Too small! (colder)",
+                "Too big!"
+            ]
+        );
+        assert_eq!(
+            feedback(&config(&[])),
+            ["Too small!", "Too small!", "Too small!", "Too big!"]
+        );
+    }
+
+    #[test]
+    fn quit_ends_the_round() {
+        let (outcome, output) = play_against(&config(&[]), 42, "10\nquit\n");
+        assert_eq!(outcome, GameOutcome::Quit);
+        assert!(output.ends_with("Thanks for playing!\n"));
+    }
+
+    #[test]
+    fn elapsed_time_switches_to_minutes_at_a_minute() {
+        assert_eq!(format_elapsed(Duration::from_millis(12_400)), "12.4s");
+        assert_eq!(format_elapsed(Duration::from_millis(59_960)), "1m00s");
+        assert_eq!(format_elapsed(Duration::from_secs(63)), "1m03s");
+    }
+}
@@ -1,40 +1,17 @@
-use rand::Rng;
-use std::cmp::Ordering;
-use std::io;
-use std::io::Write;
+use std::env;
+use std::process::ExitCode;
 
-fn main() {
-    let secret_number = rand::thread_rng().gen_range(1..=100);
-    println!("Guess the number!");
-
-    loop {
-        print!("Please input your guess: ");
-        io::stdout().flush().expect("Failed to flush stdout");
-        let mut guess = String::new();
-        io::stdin()
-            .read_line(&mut guess)
-            .expect("Failed to read line");
-
-        match guess.trim() {
-            "q" | "quit" => {
-                println!("Thanks for playing!");
-                std::process::exit(0);
-            }
-            _ => (),
-        }
-
-        let guess: u32 = match guess.trim().parse() {
-            Ok(num) => num,
-            Err(_) => continue,
-        };
-
-        match guess.cmp(&secret_number) {
-            Ordering::Less => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            Ordering::Equal => {
-                println!("{} is correct: congratulations!", guess);
-                break;
+fn main() -> ExitCode {
+    // Quiet unless RUST_LOG asks for more, e.g. RUST_LOG=debug.
+    env_logger::init();
+    match guess::run(env::args().skip(1)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // A loss has already been announced by the game itself.
+            if !matches!(err, guess::Error::Lost) {
+                eprintln!("guess: {err}");
             }
+            ExitCode::from(err.exit_code())
         }
     }
 }
@@ -0,0 +1,603 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+/// Seed 42 makes the secret 14 in the default 1–100 range.
+const SEED: &str = "42";
+
+/// A seeded, untimed game run in its own scratch config directory, so
+/// transcripts are stable and tests never touch the real leaderboard, a real
+/// `rust-book.toml`, or each other's.
+fn guess(test: &str) -> Command {
+    let mut cmd = unseeded(test);
+    cmd.args(["--seed", SEED]);
+    cmd
+}
+
+/// The same without `--seed`, for tests of GUESS_SEED.
+fn unseeded(test: &str) -> Command {
+    let config_dir = scratch_dir(test);
+    let _ = fs::remove_dir_all(&config_dir);
+    fs::create_dir_all(&config_dir).unwrap();
+    command_in(config_dir)
+}
+
+/// Another game in the directory an earlier `guess(test)` set up, so that
+/// it sees the scores recorded there.
+fn guess_again(test: &str) -> Command {
+    let mut cmd = command_in(scratch_dir(test));
+    cmd.args(["--seed", SEED]);
+    cmd
+}
+
+fn scratch_dir(test: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(test)
+}
+
+fn command_in(config_dir: PathBuf) -> Command {
+    let mut cmd = cargo_bin_cmd!("guess");
+    cmd.current_dir(&config_dir)
+        .env("XDG_CONFIG_HOME", config_dir)
+        .env_remove("GUESS_SEED")
+        .arg("--no-timer");
+    cmd
+}
+
+#[test]
+fn win_reports_attempts() {
+    guess("win_reports_attempts")
+        .write_stdin("14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Guess the number (1–100)!"))
+        .stdout(predicate::str::contains(
+            "14 is correct: you got it in 1 guess!",
+        ))
+        .stdout(predicate::str::contains("Score: 6 under optimal, lucky!"))
+        .stdout(predicate::str::contains("New record!"));
+}
+
+#[test]
+fn guess_seed_seeds_the_game() {
+    unseeded("guess_seed_seeds_the_game")
+        .env("GUESS_SEED", SEED)
+        .write_stdin("14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "14 is correct: you got it in 1 guess!",
+        ));
+}
+
+#[test]
+fn seed_flag_beats_a_bad_guess_seed() {
+    guess("seed_flag_beats_a_bad_guess_seed")
+        .env("GUESS_SEED", "not a seed")
+        .write_stdin("14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "14 is correct: you got it in 1 guess!",
+        ));
+}
+
+#[test]
+fn only_a_better_win_is_a_new_record() {
+    let test = "only_a_better_win_is_a_new_record";
+    let scores = scratch_dir(test).join("rust-guess").join("scores.json");
+    guess(test)
+        .write_stdin("50\n14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("New record!"));
+    guess_again(test)
+        .write_stdin("50\n1\n14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("New record!").not());
+    assert_eq!(
+        fs::read_to_string(&scores).unwrap(),
+        "{\n  \"1..=100\": 2\n}"
+    );
+    guess_again(test)
+        .write_stdin("14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("New record!"));
+    assert_eq!(
+        fs::read_to_string(&scores).unwrap(),
+        "{\n  \"1..=100\": 1\n}"
+    );
+}
+
+#[test]
+fn corrupt_scores_count_as_none() {
+    let test = "corrupt_scores_count_as_none";
+    let mut cmd = guess(test);
+    let scores = scratch_dir(test).join("rust-guess");
+    fs::create_dir_all(&scores).unwrap();
+    fs::write(scores.join("scores.json"), "not json").unwrap();
+    cmd.write_stdin("50\n14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("New record!"));
+    assert_eq!(
+        fs::read_to_string(scores.join("scores.json")).unwrap(),
+        "{\n  \"1..=100\": 2\n}"
+    );
+}
+
+#[test]
+fn too_small_and_too_big() {
+    guess("too_small_and_too_big")
+        .write_stdin("50\n1\n14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Too big!\n"))
+        .stdout(predicate::str::contains("Too small!\n"))
+        .stdout(predicate::str::contains("you got it in 3 guesses!"));
+}
+
+#[test]
+fn score_compares_against_binary_search() {
+    let counting_up: String = (1..=14).map(|n| format!("{n}\n")).collect();
+    guess("score_compares_against_binary_search")
+        .write_stdin(counting_up)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Score: +7 over optimal"));
+    guess("score_compares_against_binary_search")
+        .arg("--auto")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Score: optimal!"));
+}
+
+#[test]
+fn warnings_grow_as_guesses_run_out() {
+    guess("warnings_grow_as_guesses_run_out")
+        .args(["--max-guesses", "4"])
+        .write_stdin("50\n25\n10\n12\n")
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(
+            "Please input your guess (4 left): Too big!\nCareful, 3 guesses left!\n",
+        ))
+        .stdout(predicate::str::contains(
+            "Too big!\nCareful, 2 guesses left!\n",
+        ))
+        .stdout(predicate::str::contains(
+            "Too small!\nLast guess! Please input your guess: ",
+        ))
+        .stdout(predicate::str::contains("Careful, 1").not());
+    guess("warnings_grow_as_guesses_run_out")
+        .args(["--max-guesses", "3"])
+        .write_stdin("14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Careful").not());
+    guess("warnings_grow_as_guesses_run_out")
+        .args(["--max-guesses", "3", "--lang", "de"])
+        .write_stdin("50\n")
+        .assert()
+        .stdout(predicate::str::contains("Vorsicht, nur noch 2 Versuche!\n"));
+}
+
+#[test]
+fn grouped_and_malformed_guesses() {
+    guess("grouped_and_malformed_guesses")
+        .args(["--max", "2000"])
+        .write_stdin(" 1,000 \n-5\n1,2,3x\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Too big!\n"))
+        .stdout(predicate::str::contains("Guesses can't be negative.\n"))
+        .stdout(predicate::str::contains(
+            "That doesn't look like a number.\n",
+        ));
+}
+
+#[test]
+fn guesses_past_u32_are_out_of_range() {
+    guess("guesses_past_u32_are_out_of_range")
+        .write_stdin("5000000000\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Way too high — the number is at most 100.",
+        ))
+        .stdout(predicate::str::contains("negative").not());
+}
+
+#[test]
+fn empty_guesses_are_told_apart_from_junk() {
+    guess("empty_guesses_are_told_apart_from_junk")
+        .write_stdin("\n   \nabc\n14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Please type something.\n").count(2))
+        .stdout(predicate::str::contains("That doesn't look like a number.\n").count(1))
+        .stdout(predicate::str::contains("you got it in 1 guess!"));
+}
+
+#[test]
+fn color_is_off_for_no_color_unless_forced() {
+    guess("color_is_off_for_no_color_unless_forced")
+        .env("NO_COLOR", "1")
+        .write_stdin("50\nq\n")
+        .assert()
+        .stdout(predicate::str::contains("\x1b[").not());
+    guess("color_is_off_for_no_color_unless_forced")
+        .env("NO_COLOR", "1")
+        .args(["--color", "always"])
+        .write_stdin("50\nq\n")
+        .assert()
+        .stdout(predicate::str::contains("\x1b[31mToo big!\x1b[0m"));
+}
+
+#[test]
+fn prompts_follow_the_language() {
+    guess("prompts_follow_the_language")
+        .args(["--lang", "de"])
+        .write_stdin("14\nj\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Bitte gib deinen Tipp ein: "))
+        .stdout(predicate::str::contains(
+            "Noch eine Runde? (j/n) Guess the number",
+        ));
+}
+
+#[test]
+fn quit_exits_cleanly() {
+    guess("quit_exits_cleanly")
+        .write_stdin("50\nquit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Thanks for playing!\nSession summary:\n",
+        ));
+}
+
+#[test]
+fn end_of_input_exits_cleanly() {
+    guess("end_of_input_exits_cleanly")
+        .write_stdin("50\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No more input, goodbye!\nSession summary:\n",
+        ));
+}
+
+#[test]
+fn play_again_summarizes_the_session() {
+    guess("play_again_summarizes_the_session")
+        .args(["--max-guesses", "1"])
+        .write_stdin("14\ny\n1\nn\n")
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(
+            "Play again? (y/n) Guess the number",
+        ))
+        .stdout(predicate::str::ends_with(
+            "Session summary:\n  \
+             Games played: 2\n  \
+             Games won:    1\n  \
+             Gave up:      0\n  \
+             Best game:    1 guess\n  \
+             Average:      1.0 guesses per win\n  \
+             Histogram:\n    \
+             1-2 guesses: █ (1)\n",
+        ));
+}
+
+#[test]
+fn a_bad_secret_asks_again() {
+    guess("a_bad_secret_asks_again")
+        .arg("--two-player")
+        .write_stdin("500\nabc\n30\n30\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Player 1, enter the secret number (1–100): \
+             The secret must be a whole number from 1 to 100.\n\
+             Player 1, enter the secret number (1–100): \
+             The secret must be a whole number from 1 to 100.\n\
+             Player 1, enter the secret number (1–100): \
+             Guess the number (1–100)!\n",
+        ))
+        .stdout(predicate::str::contains(
+            "30 is correct: you got it in 1 guess!",
+        ));
+}
+
+#[test]
+fn histogram_buckets_the_wins() {
+    guess("histogram_buckets_the_wins")
+        .arg("--two-player")
+        .write_stdin("30\n30\ny\n80\n10\n20\n30\n40\n50\n60\n70\n80\nn\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with(
+            "  Histogram:\n    \
+             1-2 guesses: █ (1)\n    \
+             3-4 guesses: (0)\n    \
+             5-6 guesses: (0)\n    \
+             7-8 guesses: █ (1)\n",
+        ));
+    guess("histogram_buckets_the_wins")
+        .write_stdin("q\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("  Histogram:    no games won\n"));
+}
+
+#[test]
+fn giving_up_reveals_the_secret_and_loses() {
+    guess("giving_up_reveals_the_secret_and_loses")
+        .write_stdin("50\nRevEAL\nn\n")
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("The number was 14.\nPlay again?"))
+        .stdout(predicate::str::contains("Games played: 1"))
+        .stdout(predicate::str::contains("Gave up:      1"));
+}
+
+#[test]
+fn history_lists_the_round_so_far() {
+    guess("history_lists_the_round_so_far")
+        .args(["--max-guesses", "3"])
+        .write_stdin("h\n50\n1\nhistory\n14\ny\nh\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No guesses yet.").count(2))
+        .stdout(predicate::str::contains(
+            "Last guess! Please input your guess: 50 → too big, 1 → too small\n",
+        ))
+        .stdout(predicate::str::contains("in 3 guesses!"));
+}
+
+#[test]
+fn hint_narrows_with_each_guess() {
+    guess("hint_narrows_with_each_guess")
+        .write_stdin("hint\n50\n10\nhint\n30\nhint\n14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("The number is between 1 and 100."))
+        .stdout(predicate::str::contains("The number is between 11 and 49."))
+        .stdout(predicate::str::contains("The number is between 11 and 29."))
+        .stdout(predicate::str::contains("in 4 guesses!"));
+}
+
+#[test]
+fn repeated_guesses_are_flagged() {
+    guess("repeated_guesses_are_flagged")
+        .write_stdin("50\n50\n14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("You already guessed that.\n"))
+        .stdout(predicate::str::contains("you got it in 2 guesses!"));
+    guess("repeated_guesses_are_flagged")
+        .arg("--allow-repeats")
+        .write_stdin("50\n50\n14\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "You already guessed that.\nToo big!",
+        ))
+        .stdout(predicate::str::contains("you got it in 3 guesses!"));
+}
+
+#[test]
+fn replays_a_session_from_a_file() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("replay.txt");
+    fs::write(&path, "50\n25\n").unwrap();
+    guess("replays_a_session_from_a_file")
+        .arg("--from-file")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Please input your guess: 50\nToo big!\n\
+             Please input your guess: 25\nToo big!\n\
+             Please input your guess: \nNo more input, goodbye!\n",
+        ));
+}
+
+#[test]
+fn running_out_of_guesses_loses() {
+    guess("running_out_of_guesses_loses")
+        .args(["--max-guesses", "2"])
+        .write_stdin("50\n25\n")
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains(
+            "Last guess! Please input your guess: ",
+        ))
+        .stdout(predicate::str::contains(
+            "Out of guesses! The number was 14.",
+        ));
+}
+
+#[test]
+fn custom_range_is_announced_and_enforced() {
+    guess("custom_range_is_announced_and_enforced")
+        .args(["--min", "1", "--max", "1000"])
+        .write_stdin("5000\n0\n1001\n1\n1000\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Guess the number (1–1000)!"))
+        .stdout(predicate::str::contains(
+            "Way too high — the number is at most 1000.",
+        ))
+        .stdout(predicate::str::contains("That's outside the range (1–1000), try again.").count(2))
+        .stdout(predicate::str::contains("Too small!"))
+        .stdout(predicate::str::contains("Too big!"))
+        .stdout(predicate::str::contains("Games played: 0"));
+}
+
+#[test]
+fn the_whole_u32_range_has_no_outside() {
+    guess("the_whole_u32_range_has_no_outside")
+        .args(["--min", "0", "--max", "4294967295"])
+        .write_stdin("5000000000\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "That's outside the range (0–4294967295), try again.",
+        ));
+}
+
+#[test]
+fn far_below_the_range_is_way_too_low() {
+    guess("far_below_the_range_is_way_too_low")
+        .args(["--min", "500", "--max", "600"])
+        .write_stdin("10\n450\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Way too low — the number is at least 500.",
+        ))
+        .stdout(predicate::str::contains(
+            "That's outside the range (500–600), try again.",
+        ));
+}
+
+#[test]
+fn difficulty_presets_can_be_overridden() {
+    guess("difficulty_presets_can_be_overridden")
+        .args(["--difficulty", "hard"])
+        .write_stdin("q\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Guess the number (1–1000)!"))
+        .stdout(predicate::str::contains("(12 left)"));
+    guess("difficulty_presets_can_be_overridden")
+        .args(["--max", "500", "--difficulty", "hard"])
+        .write_stdin("q\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Guess the number (1–500)!"))
+        .stdout(predicate::str::contains("(12 left)"));
+}
+
+/// Like `guess`, with a `rust-book.toml` holding `config` in the scratch
+/// config directory.
+fn guess_with_config(test: &str, config: &str) -> Command {
+    let cmd = guess(test);
+    fs::write(scratch_dir(test).join("rust-book.toml"), config).unwrap();
+    cmd
+}
+
+#[test]
+fn config_file_defaults_give_way_to_flags() {
+    let test = "config_file_defaults_give_way_to_flags";
+    let config = "[guess]\ndifficulty = \"hard\"\nmax = 500\n";
+    guess_with_config(test, config)
+        .write_stdin("q\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Guess the number (1–500)!"))
+        .stdout(predicate::str::contains("(12 left)"));
+    guess_with_config(test, config)
+        .args(["--max", "50"])
+        .write_stdin("q\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Guess the number (1–50)!"));
+    guess_with_config(test, config)
+        .args(["--difficulty", "easy"])
+        .write_stdin("q\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Guess the number (1–20)!"))
+        .stdout(predicate::str::contains("left)").not());
+    guess_with_config(test, "[guess]\ndifficulty = \"hard\"\n")
+        .write_stdin("q\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Guess the number (1–1000)!"));
+}
+
+#[test]
+fn malformed_config_file_is_an_error() {
+    guess_with_config(
+        "malformed_config_file_is_an_error",
+        "[guess]\nmin = \"one\"\n",
+    )
+    .assert()
+    .code(1)
+    .stderr(predicate::str::contains("is not a valid config file"));
+    guess_with_config(
+        "malformed_config_file_is_an_error",
+        "[guess]\ndifficulty = \"extreme\"\n",
+    )
+    .assert()
+    .code(1)
+    .stderr(predicate::str::contains(
+        "'extreme' is not a valid value for difficulty in rust-book.toml",
+    ));
+}
+
+#[test]
+fn invalid_range_is_rejected() {
+    guess("invalid_range_is_rejected")
+        .args(["--min", "10", "--max", "10"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("must be less than --max"));
+}
+
+#[test]
+fn solver_wins_within_the_optimum() {
+    guess("solver_wins_within_the_optimum")
+        .arg("--auto")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Please input your guess: 50\n"))
+        .stdout(predicate::str::contains(
+            "The solver needed 7 of at most 7 steps.",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn ctrl_c_reveals_the_secret_and_exits_130() {
+    use std::io::Read;
+    use std::process::{Command, Stdio};
+
+    let config_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ctrl_c");
+    fs::create_dir_all(&config_dir).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_guess"))
+        .current_dir(&config_dir)
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .args(["--seed", SEED, "--no-timer"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Wait for the first prompt, so the handler is in place and the secret
+    // chosen before the interrupt arrives.
+    let mut stdout = child.stdout.take().unwrap();
+    let mut seen = Vec::new();
+    let mut chunk = [0; 256];
+    while !String::from_utf8_lossy(&seen).contains("your guess: ") {
+        let read = stdout.read(&mut chunk).unwrap();
+        assert!(read > 0, "guess exited before prompting");
+        seen.extend_from_slice(&chunk[..read]);
+    }
+    let killed = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success());
+
+    stdout.read_to_end(&mut seen).unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), Some(130));
+    assert!(String::from_utf8_lossy(&seen).ends_with("\nInterrupted — the number was 14. Bye!\n"));
+}
@@ -0,0 +1,54 @@
+//! Rounds played through the library, with the input and output in memory.
+
+use common::config::GuessDefaults;
+use guess::{play, Config, Error, GameOutcome};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Seed 42 makes the secret 14 in the default 1–100 range.
+fn rng() -> StdRng {
+    StdRng::seed_from_u64(42)
+}
+
+fn config(args: &[&str]) -> Config {
+    let args = ["--color", "never", "--no-timer"].iter().chain(args);
+    // No defaults, so that nobody's own rust-book.toml changes the results.
+    Config::from_args(args.map(|arg| arg.to_string()), &GuessDefaults::default()).unwrap()
+}
+
+/// Plays one round and returns how it ended along with what was printed.
+fn round(config: &Config, input: &str) -> (GameOutcome, String) {
+    let mut output = Vec::new();
+    let outcome = play(config, &mut rng(), input.as_bytes(), &mut output).unwrap();
+    (outcome, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn win_counts_the_attempts() {
+    let (outcome, output) = round(&config(&[]), "50\n14\n");
+    assert_eq!(outcome, GameOutcome::Won { attempts: 2 });
+    assert!(output.contains("Too big!\n"));
+    assert!(output.contains("14 is correct: you got it in 2 guesses!"));
+}
+
+#[test]
+fn quitting_and_running_out_of_input() {
+    assert_eq!(round(&config(&[]), "50\nq\n").0, GameOutcome::Quit);
+    assert_eq!(round(&config(&[]), "50\n").0, GameOutcome::Quit);
+}
+
+#[test]
+fn losing_and_giving_up() {
+    let limited = config(&["--max-guesses", "1"]);
+    assert_eq!(round(&limited, "50\n").0, GameOutcome::Lost);
+    assert_eq!(round(&config(&[]), "reveal\n").0, GameOutcome::GaveUp);
+}
+
+#[test]
+fn bad_arguments_are_usage_errors() {
+    let args = ["--min", "5", "--max", "5"].map(String::from);
+    assert!(matches!(
+        Config::from_args(args.into_iter(), &GuessDefaults::default()),
+        Err(Error::Usage(_))
+    ));
+}
@@ -0,0 +1,62 @@
+//! Times the iterative, fast-doubling, and matrix-power Fibonacci functions
+//! against each other. Run with `cargo bench -p fib-num`.
+//!
+//! Fast doubling and the matrix power do O(log n) work where the iterative
+//! version does O(n), but their steps are u128 multiplications rather than u64
+//! additions. Everything that fits in a u64 is small enough that the
+//! iterative version stays ahead, which the "fib" group shows.
+//!
+//! The crossover comes with larger n, so the "fib mod" group compares the two
+//! approaches mod m, where n can grow as far as it likes: `fib_mod` against
+//! a loop adding terms mod m one at a time. The loop wins at n = 10 but is
+//! already behind by n = 100, and the gap grows with every power of ten.
+
+use std::hint::black_box;
+use std::num::NonZeroU64;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fib_num::{fib_fast_doubling, fib_iterative, fib_matrix, fib_mod};
+
+const MODULUS: NonZeroU64 = NonZeroU64::new(1_000_000_007).unwrap();
+
+/// F(n) mod m one term at a time, the way `--mod` would work without fast
+/// doubling.
+fn fib_mod_iterative(n: u32, m: u64) -> u64 {
+    let (mut current, mut next) = (0, 1 % m);
+    for _ in 0..n {
+        (current, next) = (next, (current + next) % m);
+    }
+    current
+}
+
+fn compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fib");
+    for n in [10, 30, 50, 70, 90] {
+        group.bench_with_input(BenchmarkId::new("iterative", n), &n, |b, &n| {
+            b.iter(|| fib_iterative(black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new("fast doubling", n), &n, |b, &n| {
+            b.iter(|| fib_fast_doubling(black_box(n)))
+        });
+        group.bench_with_input(BenchmarkId::new("matrix", n), &n, |b, &n| {
+            b.iter(|| fib_matrix(black_box(n)))
+        });
+    }
+    group.finish();
+}
+
+fn compare_mod(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fib mod");
+    for n in [10, 100, 1_000, 10_000, 100_000] {
+        group.bench_with_input(BenchmarkId::new("iterative", n), &n, |b, &n| {
+            b.iter(|| fib_mod_iterative(black_box(n), MODULUS.get()))
+        });
+        group.bench_with_input(BenchmarkId::new("fast doubling", n), &n, |b, &n| {
+            b.iter(|| fib_mod(black_box(n), MODULUS))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compare, compare_mod);
+criterion_main!(benches);
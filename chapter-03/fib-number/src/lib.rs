@@ -0,0 +1,1195 @@
+//! The Fibonacci program from chapter 3, grown a few options.
+
+use std::error;
+use std::fmt::{self, Display};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::num::NonZeroU64;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use common::config::{FibDefaults, FILE_NAME};
+use common::messages::Lang;
+use log::debug;
+use serde_json::json;
+
+const USAGE: &str = "\
+Usage: fib-num [--list | --fast | --matrix | --sum | --mod M | --lucas | --start A,B] [--raw | --json] [INDEX...]
+       fib-num [--fast | --matrix] --ratio [--raw | --json] [INDEX...]
+       fib-num [--fast | --matrix | --mod M | --lucas | --start A,B] --time [--raw | --json] [INDEX...]
+       fib-num --repl [--raw]
+       fib-num --is-fib VALUE [--json]
+
+Any of these also take --lang en|de, and all but --repl take --output PATH.
+An INDEX can be at most 4294967295 (u32::MAX), even with --mod.";
+
+#[derive(Debug)]
+pub enum Error {
+    /// The command line could not be understood; the message says why.
+    Usage(String),
+    /// `rust-book.toml` could not be read or parsed.
+    Config(common::config::Error),
+    NotANumber(String),
+    Overflow(u32),
+    /// F(-n) for this n does not fit in an `i64`.
+    NegativeOverflow(u32),
+    LucasOverflow(u32),
+    /// The term of the sequence starting with these two values does not fit
+    /// in a `u64`.
+    StartOverflow((u64, u64), u32),
+    /// F(0) + ... + F(n) overflowed, even though F(n) itself may not have.
+    SumOverflow(u32),
+    /// Several indices were asked for and `failed` of them did. Each failure
+    /// has already been reported; `exit_code` sums them up.
+    Batch {
+        failed: usize,
+        exit_code: u8,
+    },
+    Io(io::Error),
+    /// The file given with `--output` could not be created.
+    CreateFile(PathBuf, io::Error),
+    /// The error has already been written out as JSON. This keeps its exit
+    /// code.
+    Reported(u8),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Usage(msg) => write!(f, "{msg}.\n{USAGE}"),
+            Error::Config(err) => write!(f, "{err}"),
+            Error::NotANumber(input) => {
+                write!(f, "You entered '{input}'. Try again with a number.")
+            }
+            Error::Overflow(wanted) => write!(
+                f,
+                "F({MAX_U64_INDEX}) is the largest value representable as u64; \
+                 F({wanted}) overflows."
+            ),
+            Error::NegativeOverflow(magnitude) => write!(
+                f,
+                "F(-{MAX_I64_INDEX}) is the most negative index representable as i64; \
+                 F(-{magnitude}) overflows."
+            ),
+            Error::LucasOverflow(wanted) => write!(
+                f,
+                "L({MAX_U64_LUCAS_INDEX}) is the largest Lucas number representable as u64; \
+                 L({wanted}) overflows."
+            ),
+            Error::StartOverflow((a, b), wanted) => write!(
+                f,
+                "Number {wanted} in the sequence starting {a}, {b} is too large to represent as u64."
+            ),
+            Error::SumOverflow(wanted) => write!(
+                f,
+                "The sum of F(0) through F({wanted}) is too large to represent as u64."
+            ),
+            Error::Batch { failed, .. } => write!(f, "{failed} of the requested values failed."),
+            Error::Io(err) => write!(f, "I/O error: {err}"),
+            Error::CreateFile(path, err) => {
+                write!(f, "Could not create '{}': {err}", path.display())
+            }
+            Error::Reported(_) => write!(f, "The error has already been reported."),
+        }
+    }
+}
+
+impl Error {
+    /// The status `fib-num` exits with: 1 for input it can't use, 2 for a
+    /// value too large to compute. (0 is success.)
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Usage(_)
+            | Error::Config(_)
+            | Error::NotANumber(_)
+            | Error::Io(_)
+            | Error::CreateFile(..) => 1,
+            Error::Overflow(_)
+            | Error::NegativeOverflow(_)
+            | Error::LucasOverflow(_)
+            | Error::StartOverflow(..)
+            | Error::SumOverflow(_) => 2,
+            Error::Batch { exit_code, .. } | Error::Reported(exit_code) => *exit_code,
+        }
+    }
+
+    /// The error as an object like `{"error":"overflow","index":200}`.
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Error::Usage(msg) => json!({ "error": "usage", "message": msg }),
+            Error::Config(err) => json!({ "error": "config", "message": err.to_string() }),
+            Error::NotANumber(input) => json!({ "error": "not_a_number", "input": input }),
+            Error::Overflow(wanted) => json!({ "error": "overflow", "index": wanted }),
+            Error::NegativeOverflow(magnitude) => {
+                json!({ "error": "overflow", "index": -i64::from(*magnitude) })
+            }
+            Error::LucasOverflow(wanted) | Error::StartOverflow(_, wanted) => {
+                json!({ "error": "overflow", "index": wanted })
+            }
+            Error::SumOverflow(wanted) => json!({ "error": "sum_overflow", "index": wanted }),
+            Error::Batch { failed, .. } => json!({ "error": "batch", "failed": failed }),
+            Error::Io(err) => json!({ "error": "io", "message": err.to_string() }),
+            Error::CreateFile(path, err) => json!({
+                "error": "io",
+                "path": path,
+                "message": err.to_string(),
+            }),
+            Error::Reported(_) => serde_json::Value::Null,
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// What to print for each index. At most one mode flag can be given.
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Single,
+    List,
+    Fast,
+    /// F(n) as a power of a 2x2 matrix.
+    Matrix,
+    Sum,
+    Mod(NonZeroU64),
+    /// Lucas numbers instead of Fibonacci numbers.
+    Lucas,
+    /// Any other pair of starting values for the same recurrence.
+    Start(u64, u64),
+}
+
+struct Config {
+    mode: Mode,
+    format: Format,
+    /// Results go to this file instead of stdout.
+    output: Option<PathBuf>,
+    /// Also print F(n)/F(n-1), which tends to the golden ratio.
+    ratio: bool,
+    /// Also print how long the arithmetic took.
+    time: bool,
+    repl: bool,
+    lang: Lang,
+    is_fib: Option<String>,
+    indices: Vec<String>,
+}
+
+impl Config {
+    /// Starts from the format and lang in the `[fib]` table, if any, and reads
+    /// the command line on top.
+    fn build(
+        mut args: impl Iterator<Item = String>,
+        defaults: &FibDefaults,
+    ) -> Result<Config, Error> {
+        let mut config = Config {
+            mode: Mode::Single,
+            format: Format {
+                raw: false,
+                json: false,
+            },
+            output: None,
+            ratio: false,
+            time: false,
+            repl: false,
+            lang: Lang::default(),
+            is_fib: None,
+            indices: Vec::new(),
+        };
+
+        if let Some(lang) = &defaults.lang {
+            config.lang = lang.parse().map_err(|()| {
+                Error::Usage(format!(
+                    "lang in {FILE_NAME} needs a language like 'en', not '{lang}'"
+                ))
+            })?;
+        }
+
+        while let Some(arg) = args.next() {
+            let mode = match arg.as_str() {
+                "--list" => Mode::List,
+                "--fast" => Mode::Fast,
+                "--matrix" => Mode::Matrix,
+                "--sum" => Mode::Sum,
+                "--lucas" => Mode::Lucas,
+                "--start" => {
+                    let value = args.next().unwrap_or_default();
+                    let (a, b) = value
+                        .split_once(',')
+                        .and_then(|(a, b)| Some((a.trim().parse().ok()?, b.trim().parse().ok()?)))
+                        .ok_or_else(|| {
+                            Error::Usage(format!(
+                                "--start needs two whole numbers separated by a comma, \
+                                 like 3,4, not '{value}'"
+                            ))
+                        })?;
+                    Mode::Start(a, b)
+                }
+                "--mod" => {
+                    let value = args.next().unwrap_or_default();
+                    match common::parse_number(&value).map(NonZeroU64::new) {
+                        Some(None) => {
+                            return Err(Error::Usage("--mod must be at least 1".to_string()))
+                        }
+                        Some(Some(modulus)) => Mode::Mod(modulus),
+                        None => {
+                            return Err(Error::Usage(format!(
+                                "--mod needs a whole number, not '{value}'"
+                            )))
+                        }
+                    }
+                }
+                "--raw" => {
+                    config.format.raw = true;
+                    continue;
+                }
+                "--json" => {
+                    config.format.json = true;
+                    continue;
+                }
+                "--time" => {
+                    config.time = true;
+                    continue;
+                }
+                "--ratio" => {
+                    config.ratio = true;
+                    continue;
+                }
+                "--repl" => {
+                    config.repl = true;
+                    continue;
+                }
+                "--lang" => {
+                    let value = args.next().unwrap_or_default();
+                    config.lang = value.parse().map_err(|()| {
+                        Error::Usage(format!("--lang needs a language like 'en', not '{value}'"))
+                    })?;
+                    continue;
+                }
+                "--output" => match args.next() {
+                    Some(path) => {
+                        config.output = Some(PathBuf::from(path));
+                        continue;
+                    }
+                    None => return Err(Error::Usage("--output needs a path".to_string())),
+                },
+                "--is-fib" => match args.next() {
+                    Some(value) => {
+                        config.is_fib = Some(value);
+                        continue;
+                    }
+                    None => return Err(Error::Usage("--is-fib needs a value".to_string())),
+                },
+                _ if arg.starts_with("--") => {
+                    return Err(Error::Usage(format!("Unknown argument '{arg}'")))
+                }
+                _ => {
+                    config.indices.push(arg);
+                    continue;
+                }
+            };
+            if config.mode != Mode::Single {
+                return Err(Error::Usage(
+                    "Only one of --list, --fast, --matrix, --sum, --mod, --lucas, and --start \
+                     can be used"
+                        .to_string(),
+                ));
+            }
+            config.mode = mode;
+        }
+
+        // A format from the config file only counts if no format flag was
+        // given, and is dropped where it can't apply.
+        if !config.format.raw && !config.format.json {
+            match defaults.format.as_deref() {
+                None | Some("text") => (),
+                Some("raw") => config.format.raw = true,
+                Some("json") => config.format.json = !config.repl,
+                Some(other) => {
+                    return Err(Error::Usage(format!(
+                        "format in {FILE_NAME} must be text, raw, or json, not '{other}'"
+                    )))
+                }
+            }
+        }
+
+        let other_args = config.mode != Mode::Single || !config.indices.is_empty();
+        if config.is_fib.is_some() && (other_args || config.ratio || config.time) {
+            return Err(Error::Usage(
+                "--is-fib cannot be combined with other arguments".to_string(),
+            ));
+        }
+
+        if config.format.raw && config.format.json {
+            return Err(Error::Usage(
+                "--raw and --json cannot be combined".to_string(),
+            ));
+        }
+
+        if config.ratio && !matches!(config.mode, Mode::Single | Mode::Fast | Mode::Matrix) {
+            return Err(Error::Usage(
+                "--ratio only works on its own or with --fast or --matrix".to_string(),
+            ));
+        }
+
+        if config.time && matches!(config.mode, Mode::List | Mode::Sum) {
+            return Err(Error::Usage(
+                "--time cannot be combined with --list or --sum".to_string(),
+            ));
+        }
+
+        let extras = config.format.json || config.ratio || config.time || config.output.is_some();
+        if config.repl && (other_args || extras) {
+            return Err(Error::Usage(
+                "--repl can only be combined with --raw".to_string(),
+            ));
+        }
+
+        Ok(config)
+    }
+}
+
+/// Prints the Fibonacci numbers asked for by the command-line `args` (without
+/// the program name), prompting for an index if none was given.
+///
+/// With `--json`, errors are printed as JSON too, and `Error::Reported` is
+/// returned in their place.
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), Error> {
+    let args: Vec<String> = args.collect();
+    // Checked up front so that even a bad command line, or a bad config file,
+    // gets a JSON error.
+    let mut json = args.iter().any(|arg| arg == "--json");
+    let result = common::config::load()
+        .map_err(Error::Config)
+        .and_then(|defaults| {
+            json |= defaults.fib.format.as_deref() == Some("json")
+                && !args.iter().any(|arg| arg == "--raw" || arg == "--repl");
+            Config::build(args.into_iter(), &defaults.fib)
+        })
+        .and_then(|config| run_with(&config));
+    match result {
+        Err(err) if json => {
+            println!("{}", err.to_json());
+            Err(Error::Reported(err.exit_code()))
+        }
+        result => result,
+    }
+}
+
+fn run_with(config: &Config) -> Result<(), Error> {
+    debug!("mode {:?}, indices {:?}", config.mode, config.indices);
+    let mut cache = FibCache::new();
+    if config.repl {
+        return repl(&mut cache, config.format, config.lang);
+    }
+    let Some(path) = &config.output else {
+        return answer(
+            config,
+            &mut cache,
+            &mut Output {
+                format: config.format,
+                writer: io::stdout(),
+            },
+        );
+    };
+    debug!("writing output to {}", path.display());
+    let file = File::create(path).map_err(|err| Error::CreateFile(path.clone(), err))?;
+    let mut out = Output {
+        format: config.format,
+        writer: BufWriter::new(file),
+    };
+    let answered = answer(config, &mut cache, &mut out);
+    // Flushing reports any error that dropping the writer would swallow.
+    out.writer.flush()?;
+    answered
+}
+
+/// Works out and writes everything the command line asked for.
+fn answer(
+    config: &Config,
+    cache: &mut FibCache,
+    out: &mut Output<impl Write>,
+) -> Result<(), Error> {
+    if let Some(value) = &config.is_fib {
+        return show_index_of(value, out);
+    }
+
+    let line = if config.indices.is_empty() {
+        common::prompt_line(config.lang.fib_prompt())?
+    } else {
+        config.indices.join(" ")
+    };
+    // Commas separate indices here, so unlike --is-fib values they can't be
+    // used to group digits.
+    let mut tokens: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .collect();
+    if tokens.is_empty() {
+        tokens.push("");
+    }
+
+    let parsed: Vec<Result<i64, Error>> = tokens.iter().map(|&token| parse_index(token)).collect();
+
+    // Repeated or smaller indices are answered from the cache. The fast and
+    // matrix paths compute each one directly.
+    let mut show = |wanted: Result<i64, Error>| {
+        let wanted = wanted?;
+        let Ok(wanted) = u32::try_from(wanted) else {
+            let magnitude = wanted.unsigned_abs() as u32;
+            return match config.mode {
+                _ if config.ratio => Err(Error::Usage(
+                    "--ratio needs indices that aren't negative".to_string(),
+                )),
+                Mode::Single | Mode::Fast | Mode::Matrix => {
+                    let (fib, elapsed) = timed(|| negafib(magnitude));
+                    show_negafib_for(magnitude, fib, out)?;
+                    if config.time {
+                        out.elapsed(wanted, elapsed)?;
+                    }
+                    Ok(())
+                }
+                _ => Err(Error::Usage(
+                    "Negative indices only work on their own or with --fast or --matrix"
+                        .to_string(),
+                )),
+            };
+        };
+
+        // Only the arithmetic is timed, not the printing.
+        let elapsed = match config.mode {
+            Mode::Single => {
+                let (fib, elapsed) = timed(|| cache.get(wanted).cloned());
+                show_fib_for(wanted, fib, out)?;
+                elapsed
+            }
+            Mode::Fast => {
+                let (fib, elapsed) = timed(|| fib_fast_doubling(wanted));
+                show_fib_for(wanted, fib, out)?;
+                elapsed
+            }
+            Mode::Matrix => {
+                let (fib, elapsed) = timed(|| fib_matrix(wanted));
+                show_fib_for(wanted, fib, out)?;
+                elapsed
+            }
+            Mode::List => return show_list_for(wanted, cache, out),
+            Mode::Sum => return show_sum_for(wanted, cache, out),
+            Mode::Mod(modulus) => {
+                let (fib, elapsed) = timed(|| fib_mod(wanted, modulus));
+                out.modulo(wanted, modulus.get(), fib)?;
+                elapsed
+            }
+            Mode::Lucas => {
+                let (lucas, elapsed) = timed(|| lucas(wanted));
+                out.lucas(wanted, lucas?)?;
+                elapsed
+            }
+            Mode::Start(a, b) => {
+                let (term, elapsed) = timed(|| linear_recurrence(wanted, a, b));
+                let term = term.ok_or(Error::StartOverflow((a, b), wanted))?;
+                let sequence = format!("the sequence starting {a}, {b}");
+                out.term(&sequence, wanted, term)?;
+                elapsed
+            }
+        };
+        if config.ratio {
+            out.ratio(wanted, fib_ratio(wanted))?;
+        }
+        if config.time {
+            out.elapsed(wanted.into(), elapsed)?;
+        }
+        Ok(())
+    };
+
+    // One bad value among many is reported, and the rest still get computed.
+    if parsed.len() == 1 {
+        return parsed.into_iter().try_for_each(show);
+    }
+    // The batch exits as an overflow only if every failure was one.
+    let (mut failed, mut exit_code) = (0, 0);
+    for wanted in parsed {
+        if let Err(err) = show(wanted) {
+            config.format.error(&err);
+            failed += 1;
+            exit_code = match exit_code {
+                0 => err.exit_code(),
+                code => code.min(err.exit_code()),
+            };
+        }
+    }
+    if failed > 0 {
+        return Err(Error::Batch { failed, exit_code });
+    }
+    Ok(())
+}
+
+/// The largest index whose Fibonacci number still fits in a `u64`.
+const MAX_U64_INDEX: u32 = 93;
+
+/// The largest index whose Lucas number still fits in a `u64`.
+const MAX_U64_LUCAS_INDEX: u32 = 92;
+
+/// The most negative index whose negafibonacci number still fits in an `i64`,
+/// negated.
+const MAX_I64_INDEX: u32 = 92;
+
+/// The integer type the sequence is computed with. The `bignum` feature swaps
+/// in an arbitrary-precision type that never overflows.
+#[cfg(not(feature = "bignum"))]
+type Value = u64;
+#[cfg(feature = "bignum")]
+type Value = num_bigint::BigUint;
+
+/// The arithmetic `Fibonacci` needs from an integer type.
+trait FibValue: Sized {
+    fn from_index(n: u32) -> Self;
+    /// Returns `None` if the sum does not fit in the type.
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+
+impl FibValue for u64 {
+    fn from_index(n: u32) -> Self {
+        n.into()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        u64::checked_add(*self, *other)
+    }
+}
+
+#[cfg(feature = "bignum")]
+impl FibValue for num_bigint::BigUint {
+    fn from_index(n: u32) -> Self {
+        n.into()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+}
+
+/// Prints F(wanted), where `fib` is `None` if it overflowed.
+fn show_fib_for(
+    wanted: u32,
+    fib: Option<impl Display>,
+    out: &mut Output<impl Write>,
+) -> Result<(), Error> {
+    let fib = fib.ok_or(Error::Overflow(wanted))?;
+    Ok(out.fib(wanted, fib)?)
+}
+
+/// Runs `compute` and returns its result along with how long it took.
+fn timed<T>(compute: impl FnOnce() -> T) -> (T, Duration) {
+    let started = Instant::now();
+    let result = compute();
+    (result, started.elapsed())
+}
+
+/// Prints F(-magnitude).
+fn show_negafib_for(
+    magnitude: u32,
+    fib: Option<i64>,
+    out: &mut Output<impl Write>,
+) -> Result<(), Error> {
+    let fib = fib.ok_or(Error::NegativeOverflow(magnitude))?;
+    Ok(out.fib(-i64::from(magnitude), fib)?)
+}
+
+/// Prints F(0) through F(wanted), or as far as the sequence gets before it
+/// overflows.
+fn show_list_for(
+    wanted: u32,
+    cache: &mut FibCache,
+    out: &mut Output<impl Write>,
+) -> Result<(), Error> {
+    let complete = cache.get(wanted).is_some();
+    out.list(&cache.values[..cache.values.len().min(wanted as usize + 1)])?;
+    if !complete {
+        return Err(Error::Overflow(wanted));
+    }
+    Ok(())
+}
+
+/// Prints F(0) + F(1) + ... + F(wanted), adding up the terms one by one.
+/// (It always comes out to F(wanted + 2) - 1.)
+fn show_sum_for(
+    wanted: u32,
+    cache: &mut FibCache,
+    out: &mut Output<impl Write>,
+) -> Result<(), Error> {
+    cache.get(wanted).ok_or(Error::Overflow(wanted))?;
+    let mut sum = Value::from_index(0);
+    for fib in &cache.values[..=wanted as usize] {
+        sum = FibValue::checked_add(&sum, fib).ok_or(Error::SumOverflow(wanted))?;
+    }
+    Ok(out.sum(wanted, sum)?)
+}
+
+/// Answers one index after another until "q" or end of input, reusing
+/// everything computed so far.
+fn repl(cache: &mut FibCache, format: Format, lang: Lang) -> Result<(), Error> {
+    let mut out = Output {
+        format,
+        writer: io::stdout(),
+    };
+    loop {
+        let line = match common::prompt_from(
+            &mut io::stdin().lock(),
+            &mut io::stdout(),
+            lang.fib_repl_prompt(),
+        )? {
+            Some(line) => line,
+            None => {
+                println!();
+                return Ok(());
+            }
+        };
+        if matches!(line.as_str(), "q" | "quit") {
+            return Ok(());
+        }
+        if line.is_empty() {
+            println!("{}", common::InputError::Empty);
+            continue;
+        }
+
+        let result = parse_index(&line).and_then(|wanted| match u32::try_from(wanted) {
+            Ok(wanted) => show_fib_for(wanted, cache.get(wanted), &mut out),
+            Err(_) => {
+                let magnitude = wanted.unsigned_abs() as u32;
+                show_negafib_for(magnitude, negafib(magnitude), &mut out)
+            }
+        });
+        if let Err(err) = result {
+            format.error(&err);
+        }
+    }
+}
+
+/// Parses an index, which may be negative: -n asks for the negafibonacci
+/// number F(-n). Either way the size of the index has to fit in a `u32`.
+///
+/// A 0x, 0o, or 0b prefix reads the digits after it in hexadecimal, octal, or
+/// binary, so "0x10", "0o20", "0b10000", and "16" are all the same index.
+fn parse_index(token: &str) -> Result<i64, Error> {
+    let (negative, unsigned) = match token.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, token),
+    };
+    let radix = match unsigned.get(..2).map(str::to_ascii_lowercase).as_deref() {
+        Some("0x") => Some(16),
+        Some("0o") => Some(8),
+        Some("0b") => Some(2),
+        _ => None,
+    };
+    let wanted = match radix {
+        // from_str_radix would also take a sign after the prefix.
+        Some(radix) if unsigned[2..].chars().all(|c| c.is_ascii_alphanumeric()) => {
+            i64::from_str_radix(&unsigned[2..], radix)
+                .ok()
+                .map(|wanted| if negative { -wanted } else { wanted })
+        }
+        Some(_) => None,
+        None => common::parse_number::<i64>(token),
+    };
+    wanted
+        .filter(|wanted| wanted.unsigned_abs() <= u32::MAX.into())
+        .ok_or_else(|| Error::NotANumber(token.to_string()))
+}
+
+/// Fibonacci numbers computed so far. Asking for an index past the end
+/// extends it from the last two terms; anything earlier is a lookup.
+struct FibCache {
+    values: Vec<Value>,
+}
+
+impl FibCache {
+    fn new() -> Self {
+        FibCache {
+            values: vec![Value::from_index(0), Value::from_index(1)],
+        }
+    }
+
+    /// Returns F(n), or `None` if it doesn't fit in `Value`.
+    fn get(&mut self, n: u32) -> Option<&Value> {
+        let n = n as usize;
+        if self.values.len() <= n {
+            debug!(
+                "extending the cache from {} to {} terms",
+                self.values.len(),
+                n + 1
+            );
+        }
+        while self.values.len() <= n {
+            let len = self.values.len();
+            let Some(next) = FibValue::checked_add(&self.values[len - 2], &self.values[len - 1])
+            else {
+                debug!("term {len} overflows, cache stays at {len} terms");
+                return None;
+            };
+            self.values.push(next);
+        }
+        Some(&self.values[n])
+    }
+}
+
+/// How results are written: as sentences, or with `--json` as a JSON value
+/// per line.
+#[derive(Clone, Copy)]
+struct Format {
+    raw: bool,
+    json: bool,
+}
+
+impl Format {
+    /// Errors always go to the terminal, even when results go to a file: to
+    /// stdout as JSON, or to stderr as a sentence.
+    fn error(self, err: &Error) {
+        if self.json {
+            println!("{}", err.to_json());
+        } else {
+            eprintln!("{err}");
+        }
+    }
+}
+
+/// Where every result ends up: stdout, or the file given with `--output`.
+struct Output<W> {
+    format: Format,
+    writer: W,
+}
+
+impl<W: Write> Output<W> {
+    fn fib(&mut self, index: impl Display, fib: impl Display) -> io::Result<()> {
+        self.term("the Fibonacci sequence", index, fib)
+    }
+
+    fn lucas(&mut self, index: u32, lucas: u64) -> io::Result<()> {
+        self.term("the Lucas sequence", index, lucas)
+    }
+
+    fn term(&mut self, sequence: &str, index: impl Display, value: impl Display) -> io::Result<()> {
+        if self.format.json {
+            let term = json!({ "index": number(index), "value": number(value) });
+            writeln!(self.writer, "{term}")
+        } else {
+            let value = format_value(value, self.format.raw);
+            writeln!(self.writer, "Number {index} in {sequence} is {value}.")
+        }
+    }
+
+    /// Prints F(0), F(1), ... as many as there are `fibs`.
+    fn list(&mut self, fibs: &[Value]) -> io::Result<()> {
+        if self.format.json {
+            let fibs: Vec<_> = fibs
+                .iter()
+                .enumerate()
+                .map(|(n, fib)| json!({ "index": n, "value": number(fib) }))
+                .collect();
+            writeln!(self.writer, "{}", serde_json::Value::Array(fibs))
+        } else {
+            for (n, fib) in fibs.iter().enumerate() {
+                writeln!(
+                    self.writer,
+                    "F({n}) = {}",
+                    format_value(fib, self.format.raw)
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    /// `ratio` is `None` when F(wanted - 1) is 0.
+    fn ratio(&mut self, wanted: u32, ratio: Option<f64>) -> io::Result<()> {
+        if self.format.json {
+            return writeln!(
+                self.writer,
+                "{}",
+                json!({ "index": wanted, "ratio": ratio })
+            );
+        }
+        let previous = i64::from(wanted) - 1;
+        match ratio {
+            Some(ratio) => writeln!(self.writer, "ratio F({wanted})/F({previous}) ≈ {ratio:.10}"),
+            None => writeln!(self.writer, "ratio F({wanted})/F({previous}) is undefined"),
+        }
+    }
+
+    /// Prints how long computing the value for `wanted` took, e.g.
+    /// "computed in 3.2µs".
+    fn elapsed(&mut self, wanted: i64, elapsed: Duration) -> io::Result<()> {
+        if self.format.json {
+            let nanos = elapsed.as_nanos();
+            let elapsed = json!({ "index": wanted, "computed_in_ns": nanos });
+            writeln!(self.writer, "{elapsed}")
+        } else {
+            writeln!(self.writer, "computed in {elapsed:.1?}")
+        }
+    }
+
+    fn sum(&mut self, wanted: u32, sum: impl Display) -> io::Result<()> {
+        if self.format.json {
+            writeln!(
+                self.writer,
+                "{}",
+                json!({ "index": wanted, "sum": number(sum) })
+            )
+        } else {
+            let sum = format_value(sum, self.format.raw);
+            writeln!(self.writer, "The sum of F(0) through F({wanted}) is {sum}.")
+        }
+    }
+
+    fn modulo(&mut self, wanted: u32, modulus: u64, fib: u64) -> io::Result<()> {
+        if self.format.json {
+            let fib = json!({ "index": wanted, "modulus": modulus, "value": fib });
+            writeln!(self.writer, "{fib}")
+        } else {
+            let fib = format_value(fib, self.format.raw);
+            writeln!(self.writer, "F({wanted}) mod {modulus} = {fib}")
+        }
+    }
+
+    fn index_of(&mut self, value: u64, index: Option<usize>) -> io::Result<()> {
+        if self.format.json {
+            return writeln!(self.writer, "{}", json!({ "value": value, "index": index }));
+        }
+        match index {
+            Some(n) => writeln!(self.writer, "{value} is F({n})"),
+            None => writeln!(self.writer, "{value} is not a Fibonacci number."),
+        }
+    }
+}
+
+/// Turns an integer of any size into a JSON number. Values too large for a
+/// u64 only come up with the `bignum` feature, which turns on serde_json's
+/// `arbitrary_precision` so that they come through exactly.
+fn number(value: impl Display) -> serde_json::Value {
+    let number = value
+        .to_string()
+        .parse()
+        .expect("integers are JSON numbers");
+    serde_json::Value::Number(number)
+}
+
+/// Groups digits in threes with commas, e.g. "12,586,269,025", unless `raw`
+/// output was asked for.
+fn format_value(value: impl Display, raw: bool) -> String {
+    let value = value.to_string();
+    if raw {
+        return value;
+    }
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", value.as_str()),
+    };
+    let mut grouped = String::with_capacity(value.len() + digits.len() / 3);
+    grouped.push_str(sign);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Large values may be typed with grouping commas, like "12,586,269,025".
+fn show_index_of(value: &str, out: &mut Output<impl Write>) -> Result<(), Error> {
+    let value = common::parse_number(value).ok_or_else(|| Error::NotANumber(value.to_string()))?;
+    Ok(out.index_of(value, fib_index_of(value))?)
+}
+
+/// Returns the index at which `value` appears in the sequence. Since 1 is both
+/// F(1) and F(2), the lower index, the first `position` finds, is reported for
+/// it. Listings still show F(1) and F(2) separately.
+fn fib_index_of(value: u64) -> Option<usize> {
+    Fibonacci::<u64>::new()
+        .take_while(|&fib| fib <= value)
+        .position(|fib| fib == value)
+}
+
+/// Returns F(n), or `Error::Overflow` if it does not fit in a `u64`.
+pub fn fib(n: u32) -> Result<u64, Error> {
+    fib_iterative(n).ok_or(Error::Overflow(n))
+}
+
+/// Returns the Lucas number L(n), or `Error::LucasOverflow` if it does not
+/// fit in a `u64`.
+pub fn lucas(n: u32) -> Result<u64, Error> {
+    linear_recurrence(n, 2, 1).ok_or(Error::LucasOverflow(n))
+}
+
+/// Computes F(n) in O(n) steps by adding up the sequence one term at a time.
+///
+/// Returns `None` if F(n) does not fit in a `u64`.
+pub fn fib_iterative(n: u32) -> Option<u64> {
+    linear_recurrence(n, 0, 1)
+}
+
+/// Returns the nth term of the sequence that starts `a0`, `a1` and continues
+/// with each term the sum of the two before it. Starting from 0 and 1 gives
+/// the Fibonacci numbers; starting from 2 and 1 gives the Lucas numbers.
+///
+/// Returns `None` if the term does not fit in a `u64`.
+pub fn linear_recurrence(n: u32, a0: u64, a1: u64) -> Option<u64> {
+    Fibonacci::starting(a0, a1).nth(n as usize)
+}
+
+/// Computes F(n) in O(log n) steps with the fast-doubling identities
+///
+/// ```text
+/// F(2k)   = F(k) * (2 * F(k+1) - F(k))
+/// F(2k+1) = F(k)^2 + F(k+1)^2
+/// ```
+///
+/// Returns `None` if F(n) does not fit in a `u64`.
+pub fn fib_fast_doubling(n: u32) -> Option<u64> {
+    // Returns (F(n), F(n+1)). This works in u128 because F(n+1) and the
+    // intermediate products outgrow a u64 before F(n) itself does.
+    fn pair(n: u32) -> Option<(u128, u128)> {
+        if n == 0 {
+            return Some((0, 1));
+        }
+        let (a, b) = pair(n / 2)?;
+        let even = a.checked_mul(b.checked_mul(2)?.checked_sub(a)?)?;
+        let odd = a.checked_mul(a)?.checked_add(b.checked_mul(b)?)?;
+        if n.is_multiple_of(2) {
+            Some((even, odd))
+        } else {
+            Some((odd, even.checked_add(odd)?))
+        }
+    }
+
+    let Some((fib, _)) = pair(n) else {
+        debug!("fast doubling overflowed on the way to F({n})");
+        return None;
+    };
+    u64::try_from(fib).ok()
+}
+
+/// Computes F(n) in O(log n) steps by raising a matrix to the nth power:
+///
+/// ```text
+/// [1 1]^n   [F(n+1) F(n)  ]
+/// [1 0]   = [F(n)   F(n-1)]
+/// ```
+///
+/// Returns `None` if F(n) does not fit in a `u64`.
+pub fn fib_matrix(n: u32) -> Option<u64> {
+    let power = mat_pow(Mat2([[1, 1], [1, 0]]), n);
+    let Some(Mat2([[_, fib], _])) = power else {
+        debug!("the matrix power overflowed on the way to F({n})");
+        return None;
+    };
+    u64::try_from(fib).ok()
+}
+
+/// A 2x2 matrix of u128s, wide enough to hold F(n+1) next to any F(n) that
+/// fits in a u64.
+#[derive(Clone, Copy)]
+struct Mat2([[u128; 2]; 2]);
+
+impl Mat2 {
+    const IDENTITY: Mat2 = Mat2([[1, 0], [0, 1]]);
+
+    /// The product of two matrices, or `None` if an entry overflows.
+    fn checked_mul(self, other: Mat2) -> Option<Mat2> {
+        let (a, b) = (self.0, other.0);
+        let entry = |row: usize, col: usize| {
+            a[row][0]
+                .checked_mul(b[0][col])?
+                .checked_add(a[row][1].checked_mul(b[1][col])?)
+        };
+        Some(Mat2([
+            [entry(0, 0)?, entry(0, 1)?],
+            [entry(1, 0)?, entry(1, 1)?],
+        ]))
+    }
+}
+
+/// Raises `base` to the `n`th power by repeated squaring, or returns `None` if
+/// an entry overflows along the way.
+fn mat_pow(mut base: Mat2, mut n: u32) -> Option<Mat2> {
+    let mut result = Mat2::IDENTITY;
+    while n > 0 {
+        if !n.is_multiple_of(2) {
+            result = result.checked_mul(base)?;
+        }
+        n /= 2;
+        // Squaring once more than needed could overflow for no reason.
+        if n > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+    Some(result)
+}
+
+/// Returns F(-n), using the identity F(-n) = (-1)^(n+1) F(n), or `None` if it
+/// does not fit in an `i64`.
+fn negafib(n: u32) -> Option<i64> {
+    let fib = i64::try_from(fib_fast_doubling(n)?).ok()?;
+    Some(if n.is_multiple_of(2) { -fib } else { fib })
+}
+
+/// Returns F(n) / F(n-1) as a float, or `None` for n = 1, where F(n-1) is 0.
+/// (F(-1) is 1, so the ratio for n = 0 is 0.)
+///
+/// Dividing F(n) by the term before it gives 1 + F(n-2)/F(n-1), so each ratio
+/// can be worked out from the last one without computing (or overflowing) the
+/// terms themselves. The ratios close in on the golden ratio quickly enough
+/// that after a hundred steps a float no longer changes.
+pub fn fib_ratio(n: u32) -> Option<f64> {
+    match n {
+        0 => return Some(0.0),
+        1 => return None,
+        _ => (),
+    }
+    // F(2) / F(1)
+    let mut ratio = 1.0;
+    for _ in 2..n.min(100) {
+        ratio = 1.0 + 1.0 / ratio;
+    }
+    Some(ratio)
+}
+
+/// Computes F(n) mod `m` with the same fast-doubling identities as
+/// `fib_fast_doubling`, reducing mod `m` at every step so that nothing
+/// overflows however large n is. Nothing is left over mod 0, so `m` can't be
+/// 0.
+pub fn fib_mod(n: u32, m: NonZeroU64) -> u64 {
+    // Returns (F(n) mod m, F(n+1) mod m). Both stay below m, so every product
+    // of two of them fits in a u128.
+    fn pair(n: u32, m: u128) -> (u128, u128) {
+        if n == 0 {
+            return (0, 1 % m);
+        }
+        let (a, b) = pair(n / 2, m);
+        let even = a * ((2 * b + m - a) % m) % m;
+        let odd = (a * a % m + b * b % m) % m;
+        if n.is_multiple_of(2) {
+            (even, odd)
+        } else {
+            (odd, (even + odd) % m)
+        }
+    }
+
+    let (fib, _) = pair(n, m.get().into());
+    fib as u64
+}
+
+/// Yields F(0), F(1), F(2), ... and stops before the first value that
+/// overflows `T`. Other starting values give other sequences with the same
+/// recurrence.
+struct Fibonacci<T> {
+    current: Option<T>,
+    next: Option<T>,
+}
+
+impl<T: FibValue> Fibonacci<T> {
+    fn new() -> Self {
+        Fibonacci::starting(T::from_index(0), T::from_index(1))
+    }
+
+    fn starting(first: T, second: T) -> Self {
+        Fibonacci {
+            current: Some(first),
+            next: Some(second),
+        }
+    }
+}
+
+impl<T: FibValue> Iterator for Fibonacci<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let current = self.current.take()?;
+        let after = self
+            .next
+            .as_ref()
+            .and_then(|next| current.checked_add(next));
+        self.current = self.next.take();
+        self.next = after;
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "bignum"))]
+    #[test]
+    fn overflow_begins_after_f93() {
+        let mut cache = FibCache::new();
+        assert_eq!(cache.get(93), Some(&12_200_160_415_121_876_738));
+        assert_eq!(cache.get(94), None);
+        assert_eq!(
+            Error::Overflow(94).to_string(),
+            "F(93) is the largest value representable as u64; F(94) overflows."
+        );
+    }
+
+    #[test]
+    fn fib_mod_matches_the_plain_value() {
+        for m in [1, 2, 7, 10, 1000, u64::MAX] {
+            let nonzero = NonZeroU64::new(m).unwrap();
+            for n in 0..=93 {
+                assert_eq!(
+                    fib_mod(n, nonzero),
+                    fib_iterative(n).unwrap() % m,
+                    "F({n}) mod {m}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fib_mod_repeats_with_the_pisano_period() {
+        // The Pisano period for 10 is 60, so the last digit repeats every 60
+        // terms.
+        let ten = NonZeroU64::new(10).unwrap();
+        for n in 0..1000 {
+            assert_eq!(fib_mod(n, ten), fib_mod(n + 60, ten));
+        }
+        assert!((1..60).any(|n| fib_mod(n, ten) != fib_mod(n + 30, ten)));
+    }
+
+    #[test]
+    fn cache_grows_once_and_then_looks_up() {
+        let mut cache = FibCache::new();
+        assert_eq!(cache.get(50), Some(&Value::from(12_586_269_025u64)));
+        assert_eq!(cache.values.len(), 51);
+        assert_eq!(cache.get(30), Some(&Value::from(832_040u64)));
+        assert_eq!(cache.values.len(), 51);
+    }
+
+    #[test]
+    fn format_value_groups_digits_in_threes() {
+        let digits = "12345678901234567890";
+        for len in 1..=20 {
+            let value = &digits[..len];
+            // Counted from the right, so the first group is the short one.
+            let first = match len % 3 {
+                0 => 3,
+                short => short,
+            };
+            let mut expected = value[..first].to_string();
+            for group in value.as_bytes()[first..].chunks(3) {
+                expected.push(',');
+                expected.push_str(std::str::from_utf8(group).unwrap());
+            }
+            assert_eq!(format_value(value, false), expected);
+            assert_eq!(
+                format_value(format!("-{value}"), false),
+                format!("-{expected}")
+            );
+            assert_eq!(format_value(value, true), value);
+        }
+        assert_eq!(format_value(-1234, false), "-1,234");
+        assert_eq!(format_value(12_586_269_025u64, false), "12,586,269,025");
+    }
+}
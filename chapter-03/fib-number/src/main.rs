@@ -1,34 +1,16 @@
-use std::io;
-use std::io::Write;
+use std::env;
+use std::process::ExitCode;
 
-fn main() {
-    print!("What number in the Fibonacci sequence do you want to see? ");
-    io::stdout().flush().expect("Failed to flush stdout");
-    let mut wanted = String::new();
-
-    io::stdin()
-        .read_line(&mut wanted)
-        .expect("Failed to read line");
-    let wanted = wanted.trim();
-    let wanted: u32 = if let Ok(num) = wanted.parse() {
-        num
-    } else {
-        println!("You entered '{wanted}'. Try again with a number.");
-        std::process::exit(1);
-    };
-
-    show_fib_for(wanted);
-}
-
-fn show_fib_for(wanted: u32) {
-    if wanted < 2 {
-        println!("Number {wanted} in the Fibonacci sequence is {wanted}.");
-        return;
-    }
-    let mut fib: u32 = 1;
-    let mut fib_prev: u32 = 1;
-    for _ in 2..wanted {
-        (fib_prev, fib) = (fib, fib_prev + fib);
+fn main() -> ExitCode {
+    // Quiet unless RUST_LOG asks for more, e.g. RUST_LOG=debug.
+    env_logger::init();
+    match fib_num::run(env::args().skip(1)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            if !matches!(err, fib_num::Error::Reported(_)) {
+                eprintln!("{err}");
+            }
+            ExitCode::from(err.exit_code())
+        }
     }
-    println!("Number {wanted} in the Fibonacci sequence is {fib}.");
 }
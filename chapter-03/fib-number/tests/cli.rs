@@ -0,0 +1,386 @@
+use assert_cmd::cargo::cargo_bin_cmd;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// `fib-num` run from an empty scratch directory, with that as its config
+/// directory too, so that a real `rust-book.toml` can't change the output.
+fn fib_num() -> Command {
+    let scratch = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("fib-scratch");
+    std::fs::create_dir_all(&scratch).unwrap();
+    let mut cmd = cargo_bin_cmd!("fib-num");
+    cmd.current_dir(&scratch).env("XDG_CONFIG_HOME", &scratch);
+    cmd
+}
+
+#[test]
+fn index_from_argument() {
+    fib_num()
+        .arg("20")
+        .assert()
+        .success()
+        .stdout("Number 20 in the Fibonacci sequence is 6,765.\n");
+}
+
+#[test]
+fn index_from_prompt() {
+    fib_num()
+        .write_stdin("10\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with(
+            "Number 10 in the Fibonacci sequence is 55.\n",
+        ));
+}
+
+#[test]
+fn several_indices_in_order() {
+    fib_num().args(["5", "10", "0"]).assert().success().stdout(
+        "Number 5 in the Fibonacci sequence is 5.\n\
+             Number 10 in the Fibonacci sequence is 55.\n\
+             Number 0 in the Fibonacci sequence is 0.\n",
+    );
+}
+
+#[test]
+fn not_a_number_fails() {
+    fib_num()
+        .write_stdin("abc\n")
+        .assert()
+        .code(1)
+        .stderr("You entered 'abc'. Try again with a number.\n");
+}
+
+// Without `bignum` the values are u64s, and these overflow.
+#[cfg(not(feature = "bignum"))]
+#[test]
+fn overflow_fails() {
+    fib_num()
+        .arg("94")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("F(94) overflows."));
+}
+
+#[test]
+fn largest_u64_value() {
+    fib_num()
+        .args(["93", "--raw"])
+        .assert()
+        .success()
+        .stdout("Number 93 in the Fibonacci sequence is 12200160415121876738.\n");
+}
+
+#[test]
+fn matrix_power_overflows_at_the_same_index() {
+    fib_num()
+        .args(["--matrix", "--raw", "93"])
+        .assert()
+        .success()
+        .stdout("Number 93 in the Fibonacci sequence is 12200160415121876738.\n");
+    fib_num()
+        .args(["--matrix", "94"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("F(94) overflows."));
+}
+
+#[cfg(feature = "bignum")]
+#[test]
+fn bignum_gives_exact_digits_past_u64() {
+    fib_num()
+        .args(["100", "--raw"])
+        .assert()
+        .success()
+        .stdout("Number 100 in the Fibonacci sequence is 354224848179261915075.\n");
+}
+
+#[test]
+fn list() {
+    fib_num()
+        .args(["--list", "3"])
+        .assert()
+        .success()
+        .stdout("F(0) = 0\nF(1) = 1\nF(2) = 1\nF(3) = 2\n");
+    fib_num()
+        .args(["--list", "0"])
+        .assert()
+        .success()
+        .stdout("F(0) = 0\n");
+}
+
+#[test]
+fn is_fib() {
+    fib_num()
+        .args(["--is-fib", "89"])
+        .assert()
+        .success()
+        .stdout("89 is F(11)\n");
+    fib_num()
+        .args(["--is-fib", "90"])
+        .assert()
+        .success()
+        .stdout("90 is not a Fibonacci number.\n");
+    fib_num()
+        .args(["--is-fib", "0"])
+        .assert()
+        .success()
+        .stdout("0 is F(0)\n");
+}
+
+#[test]
+fn one_is_both_f1_and_f2() {
+    fib_num().args(["1", "2"]).assert().success().stdout(
+        "Number 1 in the Fibonacci sequence is 1.\n\
+             Number 2 in the Fibonacci sequence is 1.\n",
+    );
+    fib_num()
+        .args(["--list", "2"])
+        .assert()
+        .success()
+        .stdout("F(0) = 0\nF(1) = 1\nF(2) = 1\n");
+    fib_num()
+        .args(["--is-fib", "1"])
+        .assert()
+        .success()
+        .stdout("1 is F(1)\n");
+}
+
+#[test]
+fn empty_input_is_asked_for_again() {
+    fib_num()
+        .write_stdin("\n   \nabc\n")
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("Please type something.\n").count(2))
+        .stderr("You entered 'abc'. Try again with a number.\n");
+    fib_num()
+        .arg("--repl")
+        .write_stdin("\n   \nabc\n10\nq\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Please type something.\n").count(2))
+        .stdout(predicate::str::contains("is 55."))
+        .stderr("You entered 'abc'. Try again with a number.\n");
+}
+
+#[test]
+fn repl_answers_until_quit() {
+    fib_num()
+        .arg("--repl")
+        .write_stdin("50\n30\nx\nq\n")
+        .assert()
+        .success()
+        .stdout(
+            "Index (or q to quit): Number 50 in the Fibonacci sequence is 12,586,269,025.\n\
+             Index (or q to quit): Number 30 in the Fibonacci sequence is 832,040.\n\
+             Index (or q to quit): Index (or q to quit): ",
+        )
+        .stderr("You entered 'x'. Try again with a number.\n");
+}
+
+#[test]
+fn sum_matches_closed_form() {
+    // F(0) + ... + F(n) == F(n + 2) - 1, and F(12) is 144.
+    fib_num()
+        .args(["--sum", "10"])
+        .assert()
+        .success()
+        .stdout("The sum of F(0) through F(10) is 143.\n");
+}
+
+#[cfg(not(feature = "bignum"))]
+#[test]
+fn sum_overflows_before_its_last_term() {
+    fib_num()
+        .args(["--sum", "92"])
+        .assert()
+        .code(2)
+        .stderr("The sum of F(0) through F(92) is too large to represent as u64.\n");
+}
+
+#[test]
+fn modulus_handles_huge_indices() {
+    fib_num()
+        .args(["--mod", "1000", "1000000000"])
+        .assert()
+        .success()
+        .stdout("F(1000000000) mod 1000 = 875\n");
+    fib_num().args(["--mod", "0", "5"]).assert().code(1);
+}
+
+#[test]
+fn negative_indices_are_negafibonacci() {
+    fib_num()
+        .args(["-1", "-2", "-6", "-7"])
+        .assert()
+        .success()
+        .stdout(
+            "Number -1 in the Fibonacci sequence is 1.\n\
+             Number -2 in the Fibonacci sequence is -1.\n\
+             Number -6 in the Fibonacci sequence is -8.\n\
+             Number -7 in the Fibonacci sequence is 13.\n",
+        );
+    fib_num()
+        .args(["-93"])
+        .assert()
+        .code(2)
+        .stderr("F(-92) is the most negative index representable as i64; F(-93) overflows.\n");
+}
+
+#[test]
+fn json_output_and_errors() {
+    fib_num()
+        .args(["20", "--json"])
+        .assert()
+        .success()
+        .stdout("{\"index\":20,\"value\":6765}\n");
+    fib_num()
+        .args(["--list", "2", "--json"])
+        .assert()
+        .success()
+        .stdout(
+            "[{\"index\":0,\"value\":0},{\"index\":1,\"value\":1},{\"index\":2,\"value\":1}]\n",
+        );
+    fib_num()
+        .args(["x", "--json"])
+        .assert()
+        .code(1)
+        .stdout("{\"error\":\"not_a_number\",\"input\":\"x\"}\n")
+        .stderr("");
+}
+
+#[cfg(not(feature = "bignum"))]
+#[test]
+fn json_overflow_error() {
+    fib_num()
+        .args(["200", "--json"])
+        .assert()
+        .code(2)
+        .stdout("{\"error\":\"overflow\",\"index\":200}\n")
+        .stderr("");
+}
+
+#[test]
+fn lucas() {
+    fib_num()
+        .args(["--lucas", "5"])
+        .assert()
+        .success()
+        .stdout("Number 5 in the Lucas sequence is 11.\n");
+}
+
+#[test]
+fn output_goes_to_a_file() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("fib-output.txt");
+    fib_num()
+        .args(["--list", "3", "--output"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "F(0) = 0\nF(1) = 1\nF(2) = 1\nF(3) = 2\n"
+    );
+}
+
+#[test]
+fn time_follows_the_result() {
+    fib_num().args(["--time", "20"]).assert().success().stdout(
+        predicate::str::is_match(
+            "^Number 20 in the Fibonacci sequence is 6,765.\ncomputed in [0-9.]+(ns|µs|ms|s)\n$",
+        )
+        .unwrap(),
+    );
+}
+
+#[test]
+fn indices_in_other_bases() {
+    for index in ["16", "0x10", "0o20", "0b10000"] {
+        fib_num()
+            .arg(index)
+            .assert()
+            .success()
+            .stdout("Number 16 in the Fibonacci sequence is 987.\n");
+    }
+    fib_num()
+        .arg("0b102")
+        .assert()
+        .code(1)
+        .stderr("You entered '0b102'. Try again with a number.\n");
+}
+
+#[test]
+fn start_seeds_the_recurrence() {
+    fib_num()
+        .args(["--start", "3,4", "4"])
+        .assert()
+        .success()
+        .stdout("Number 4 in the sequence starting 3, 4 is 18.\n");
+    fib_num()
+        .args(["--start", "2,1", "5"])
+        .assert()
+        .success()
+        .stdout("Number 5 in the sequence starting 2, 1 is 11.\n");
+    fib_num()
+        .args(["--start", "0,1", "20"])
+        .assert()
+        .success()
+        .stdout("Number 20 in the sequence starting 0, 1 is 6,765.\n");
+    fib_num()
+        .args(["--start", "1,2,3", "5"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::starts_with(
+            "--start needs two whole numbers separated by a comma, like 3,4, not '1,2,3'.",
+        ));
+}
+
+#[cfg(not(feature = "bignum"))]
+#[test]
+fn batch_exit_code_reflects_the_failures() {
+    // Overflow alone exits with 2, but any unusable input makes it 1.
+    fib_num().args(["5", "100", "200"]).assert().code(2);
+    fib_num().args(["5", "100", "x"]).assert().code(1);
+    fib_num().args(["--json", "100"]).assert().code(2);
+}
+
+#[test]
+fn config_file_defaults_give_way_to_flags() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("fib-config");
+    std::fs::create_dir_all(&dir).unwrap();
+    let fib = |config: &str, args: &[&str]| {
+        std::fs::write(dir.join("rust-book.toml"), config).unwrap();
+        let mut cmd = fib_num();
+        cmd.current_dir(&dir)
+            .env("XDG_CONFIG_HOME", &dir)
+            .args(args);
+        cmd
+    };
+
+    fib("[fib]\nformat = \"raw\"\n", &["20"])
+        .assert()
+        .success()
+        .stdout("Number 20 in the Fibonacci sequence is 6765.\n");
+    fib("[fib]\nformat = \"raw\"\n", &["20", "--json"])
+        .assert()
+        .success()
+        .stdout("{\"index\":20,\"value\":6765}\n");
+    fib("[fib]\n", &["20"])
+        .assert()
+        .success()
+        .stdout("Number 20 in the Fibonacci sequence is 6,765.\n");
+    fib("[fib]\nformat = \"json\"\n", &["x"])
+        .assert()
+        .code(1)
+        .stdout("{\"error\":\"not_a_number\",\"input\":\"x\"}\n");
+    fib("[fib]\nformat = 3\n", &["20"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("is not a valid config file"))
+        .stderr(predicate::str::contains("Usage:").not());
+    fib("[fib]\nformat = 3\n", &["20", "--json"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::starts_with("{\"error\":\"config\","));
+}
@@ -0,0 +1,85 @@
+//! Property tests for the Fibonacci functions the library exposes, over the
+//! indices whose values fit in a u64.
+
+use fib_num::{
+    fib, fib_fast_doubling, fib_iterative, fib_matrix, fib_ratio, linear_recurrence, lucas, Error,
+};
+use proptest::prelude::*;
+
+#[test]
+fn base_cases() {
+    assert_eq!(fib_iterative(0), Some(0));
+    assert_eq!(fib_iterative(1), Some(1));
+}
+
+#[test]
+fn overflow_is_an_error() {
+    assert_eq!(fib(93).unwrap(), 12_200_160_415_121_876_738);
+    assert!(matches!(fib(94), Err(Error::Overflow(94))));
+    assert_eq!(lucas(92).unwrap(), 16_860_207_025_497_407_047);
+    assert!(matches!(lucas(93), Err(Error::LucasOverflow(93))));
+}
+
+#[test]
+fn lucas_numbers() {
+    let lucas: Vec<_> = (0..10)
+        .map(|n| linear_recurrence(n, 2, 1).unwrap())
+        .collect();
+    assert_eq!(lucas, [2, 1, 3, 4, 7, 11, 18, 29, 47, 76]);
+}
+
+#[test]
+fn ratio_of_the_first_terms() {
+    assert_eq!(fib_ratio(0), Some(0.0));
+    assert_eq!(fib_ratio(1), None);
+    assert_eq!(fib_ratio(2), Some(1.0));
+    assert_eq!(fib_ratio(3), Some(2.0));
+}
+
+/// Every index that fits, and the first that doesn't.
+#[test]
+fn fast_doubling_agrees() {
+    for n in 0..=94 {
+        assert_eq!(fib_fast_doubling(n), fib_iterative(n), "F({n})");
+    }
+}
+
+#[test]
+fn matrix_power_agrees() {
+    for n in 0..=94 {
+        let matrix = fib_matrix(n);
+        assert_eq!(matrix, fib_fast_doubling(n), "F({n})");
+        assert_eq!(matrix, fib_iterative(n), "F({n})");
+    }
+}
+
+proptest! {
+    #[test]
+    fn each_term_is_the_sum_of_the_two_before(n in 2..=92u32) {
+        let fib = |n| fib_iterative(n).unwrap();
+        prop_assert_eq!(fib(n), fib(n - 1) + fib(n - 2));
+    }
+
+    #[test]
+    fn never_decreases(n in 1..=92u32) {
+        prop_assert!(fib_iterative(n).unwrap() >= fib_iterative(n - 1).unwrap());
+    }
+
+    #[test]
+    fn seeds_zero_and_one_give_fibonacci(n in 0..=93u32) {
+        prop_assert_eq!(linear_recurrence(n, 0, 1), fib_fast_doubling(n));
+    }
+
+    #[test]
+    fn all_three_overflow_together(n in 94..=10_000u32) {
+        prop_assert_eq!(fib_iterative(n), None);
+        prop_assert_eq!(fib_fast_doubling(n), None);
+        prop_assert_eq!(fib_matrix(n), None);
+    }
+
+    #[test]
+    fn ratio_approaches_the_golden_ratio(n in 20..=200u32) {
+        let phi = (1.0 + 5f64.sqrt()) / 2.0;
+        prop_assert!((fib_ratio(n).unwrap() - phi).abs() < 1e-7);
+    }
+}
@@ -0,0 +1,97 @@
+//! A tiny ANSI helper so output can be colored without a dependency. Every
+//! program that colors anything decides whether to through `ColorChoice`.
+
+use std::env;
+use std::ffi::OsStr;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+
+#[derive(Clone, Copy)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// `Auto` only colors output when stdout is a terminal and `NO_COLOR`
+    /// isn't set (see <https://no-color.org>), so piped output stays plain
+    /// text. `Always` wins over both.
+    pub fn enabled(self) -> bool {
+        self.decide(
+            env::var_os("NO_COLOR").as_deref(),
+            io::stdout().is_terminal(),
+        )
+    }
+
+    /// The decision `enabled` makes, given the value of `NO_COLOR` and
+    /// whether stdout is a terminal.
+    fn decide(self, no_color: Option<&OsStr>, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => no_color.is_none_or(OsStr::is_empty) && terminal,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+impl Color {
+    fn code(self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Blue => 34,
+        }
+    }
+}
+
+/// Wraps `text` in the escape codes for `color`, or returns it untouched.
+pub fn paint(text: &str, color: Color, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[{}m{text}\x1b[0m", color.code())
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_honors_no_color_on_a_terminal() {
+        let set = Some(OsStr::new("1"));
+        assert!(ColorChoice::Auto.decide(None, true));
+        assert!(!ColorChoice::Auto.decide(set, true));
+        // An empty NO_COLOR counts as unset.
+        assert!(ColorChoice::Auto.decide(Some(OsStr::new("")), true));
+        assert!(!ColorChoice::Auto.decide(None, false));
+    }
+
+    #[test]
+    fn always_and_never_ignore_the_environment() {
+        let set = Some(OsStr::new("1"));
+        assert!(ColorChoice::Always.decide(set, false));
+        assert!(!ColorChoice::Never.decide(None, true));
+    }
+}
@@ -0,0 +1,102 @@
+//! Defaults read from a `rust-book.toml`, so that flags used every time don't
+//! have to be typed every time. A file looks like
+//!
+//! ```toml
+//! [guess]
+//! difficulty = "hard"
+//! lang = "de"
+//!
+//! [fib]
+//! format = "raw"
+//! ```
+//!
+//! Each program applies these on top of its built-in defaults, and its
+//! command-line flags on top of these.
+
+use serde::Deserialize;
+use std::env;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+pub const FILE_NAME: &str = "rust-book.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Defaults {
+    pub guess: GuessDefaults,
+    pub fib: FibDefaults,
+}
+
+/// The `[guess]` table. Values are checked by `guess` itself, the same way
+/// as the flags they stand in for.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct GuessDefaults {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    pub max_guesses: Option<u32>,
+    pub difficulty: Option<String>,
+    pub lang: Option<String>,
+}
+
+/// The `[fib]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FibDefaults {
+    /// "text", "raw", or "json".
+    pub format: Option<String>,
+    pub lang: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Read(path, err) => write!(f, "cannot read '{}': {err}", path.display()),
+            Error::Parse(path, err) => {
+                write!(
+                    f,
+                    "'{}' is not a valid config file: {}",
+                    path.display(),
+                    err.message()
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Where per-user files for these programs go: `$XDG_CONFIG_HOME`, or
+/// `~/.config` without it.
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+/// Reads the first `rust-book.toml` found in the current directory or the
+/// config directory. No file at all means no defaults; a file that can't be
+/// read or parsed is an error rather than silently ignored.
+pub fn load() -> Result<Defaults, Error> {
+    let candidates = [
+        Some(PathBuf::from(FILE_NAME)),
+        config_dir().map(|dir| dir.join(FILE_NAME)),
+    ];
+    for path in candidates.into_iter().flatten() {
+        match fs::read_to_string(&path) {
+            Ok(text) => return toml::from_str(&text).map_err(|err| Error::Parse(path, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(Error::Read(path, err)),
+        }
+    }
+    Ok(Defaults::default())
+}
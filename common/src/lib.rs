@@ -0,0 +1,128 @@
+//! Input and output helpers shared by the programs in this repo.
+
+pub mod color;
+pub mod config;
+pub mod messages;
+
+use std::fmt::{self, Display};
+use std::io;
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+/// Why a line of input could not be used as a number.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputError {
+    /// Nothing but whitespace was typed.
+    Empty,
+    NotANumber,
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Empty => write!(f, "Please type something."),
+            InputError::NotANumber => write!(f, "That doesn't look like a number."),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Reads one line from `input` with surrounding whitespace trimmed. Returns
+/// `None` at end of input.
+pub fn read_line(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// Writes `prompt` and reads the answer from `input`. The output is flushed
+/// first so the prompt shows up before the user starts typing.
+pub fn prompt_from(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+) -> io::Result<Option<String>> {
+    write!(output, "{prompt}")?;
+    output.flush()?;
+    read_line(input)
+}
+
+/// Prompts on stdout and reads a trimmed line from stdin, asking again while
+/// the line is empty. End of input reads as an empty line.
+pub fn prompt_line(prompt: &str) -> io::Result<String> {
+    prompt_until_typed(&mut io::stdin().lock(), &mut io::stdout(), prompt)
+}
+
+fn prompt_until_typed(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+) -> io::Result<String> {
+    while let Some(answer) = prompt_from(input, output, prompt)? {
+        if !answer.is_empty() {
+            return Ok(answer);
+        }
+        writeln!(output, "{}", InputError::Empty)?;
+    }
+    Ok(String::new())
+}
+
+/// Parses a number the way people tend to type it: surrounding whitespace is
+/// ignored and commas may group the digits, so " 1,000 " reads as 1000.
+/// Returns `None` for anything else, including "1,2,3x" or a stray leading or
+/// trailing comma.
+pub fn parse_number<T: FromStr>(input: &str) -> Option<T> {
+    let input = input.trim();
+    if input.starts_with(',') || input.ends_with(',') {
+        return None;
+    }
+    input.replace(',', "").parse().ok()
+}
+
+/// Like `parse_number`, but says whether the input was empty or just not a
+/// number, since the two deserve different replies.
+pub fn parse_input<T: FromStr>(input: &str) -> Result<T, InputError> {
+    if input.trim().is_empty() {
+        return Err(InputError::Empty);
+    }
+    parse_number(input).ok_or(InputError::NotANumber)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_number_allows_grouping_commas() {
+        assert_eq!(parse_number::<u32>(" 1,000 "), Some(1000));
+        assert_eq!(parse_number::<u32>("42"), Some(42));
+        assert_eq!(parse_number::<u32>("1,2,3x"), None);
+        assert_eq!(parse_number::<u32>(",100"), None);
+        assert_eq!(parse_number::<u32>("100,"), None);
+    }
+
+    #[test]
+    fn parse_input_tells_empty_from_junk() {
+        assert_eq!(parse_input::<u32>("7"), Ok(7));
+        assert_eq!(parse_input::<u32>(""), Err(InputError::Empty));
+        assert_eq!(parse_input::<u32>("   "), Err(InputError::Empty));
+        assert_eq!(parse_input::<u32>("abc"), Err(InputError::NotANumber));
+    }
+
+    #[test]
+    fn prompt_asks_again_until_something_is_typed() {
+        let mut output = Vec::new();
+        let answer = prompt_until_typed(&mut "\n  \nabc\n".as_bytes(), &mut output, "> ");
+        assert_eq!(answer.unwrap(), "abc");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "> Please type something.\n> Please type something.\n> "
+        );
+
+        let answer = prompt_until_typed(&mut "\n".as_bytes(), &mut Vec::new(), "> ");
+        assert_eq!(answer.unwrap(), "");
+    }
+}
@@ -0,0 +1,87 @@
+//! The prompts the programs show, in each language they can be shown in.
+//! Adding a language means adding a `Lang` variant and a translation to each
+//! method below.
+
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Lang {
+    #[default]
+    English,
+    German,
+}
+
+impl FromStr for Lang {
+    type Err = ();
+
+    /// Accepts codes like "en" or "de", as well as `LANG`-style values such
+    /// as "de_DE.UTF-8".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split(['_', '.', '-']).next() {
+            Some("en") => Ok(Lang::English),
+            Some("de") => Ok(Lang::German),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Lang {
+    /// Asks for a guess, saying how many are left if there is a limit, and
+    /// that it's the last one when it is.
+    pub fn guess_prompt(self, left: Option<u32>) -> String {
+        match (self, left) {
+            (Lang::English, Some(1)) => "Last guess! Please input your guess: ".to_string(),
+            (Lang::English, Some(left)) => format!("Please input your guess ({left} left): "),
+            (Lang::English, None) => "Please input your guess: ".to_string(),
+            (Lang::German, Some(1)) => "Letzter Versuch! Bitte gib deinen Tipp ein: ".to_string(),
+            (Lang::German, Some(left)) => format!("Bitte gib deinen Tipp ein (noch {left}): "),
+            (Lang::German, None) => "Bitte gib deinen Tipp ein: ".to_string(),
+        }
+    }
+
+    /// Warns that the guesses are running out, with `left` of them to go.
+    pub fn few_guesses_left(self, left: u32) -> String {
+        match self {
+            Lang::English => format!("Careful, {left} guesses left!"),
+            Lang::German => format!("Vorsicht, nur noch {left} Versuche!"),
+        }
+    }
+
+    pub fn secret_prompt(self, min: u32, max: u32) -> String {
+        match self {
+            Lang::English => format!("Player 1, enter the secret number ({min}–{max}): "),
+            Lang::German => format!("Spieler 1, gib die geheime Zahl ein ({min}–{max}): "),
+        }
+    }
+
+    pub fn play_again_prompt(self) -> &'static str {
+        match self {
+            Lang::English => "Play again? (y/n) ",
+            Lang::German => "Noch eine Runde? (j/n) ",
+        }
+    }
+
+    /// Whether `answer` to a yes/no prompt means yes. English answers are
+    /// understood in every language.
+    pub fn is_yes(self, answer: &str) -> bool {
+        let answer = answer.to_lowercase();
+        match self {
+            Lang::English => matches!(answer.as_str(), "y" | "yes"),
+            Lang::German => matches!(answer.as_str(), "y" | "yes" | "j" | "ja"),
+        }
+    }
+
+    pub fn fib_prompt(self) -> &'static str {
+        match self {
+            Lang::English => "What number in the Fibonacci sequence do you want to see? ",
+            Lang::German => "Welche Zahl der Fibonacci-Folge möchtest du sehen? ",
+        }
+    }
+
+    pub fn fib_repl_prompt(self) -> &'static str {
+        match self {
+            Lang::English => "Index (or q to quit): ",
+            Lang::German => "Index (oder q zum Beenden): ",
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::io;
+
+const MENU: &str = "\
+Programs from The Rust Programming Language:
+  1) Guessing game
+  2) Fibonacci";
+
+fn main() -> io::Result<()> {
+    println!("{MENU}");
+    loop {
+        let choice = common::prompt_from(
+            &mut io::stdin().lock(),
+            &mut io::stdout(),
+            "Choose a program (or q to quit): ",
+        )?;
+
+        // Each program runs with its defaults, as if started with no arguments.
+        match choice.as_deref() {
+            Some("1") => {
+                match guess::run(std::iter::empty()) {
+                    // A loss has already been announced by the game itself.
+                    Ok(()) | Err(guess::Error::Lost) => (),
+                    Err(err) => eprintln!("guess: {err}"),
+                }
+            }
+            Some("2") => match fib_num::run(std::iter::empty()) {
+                Ok(()) | Err(fib_num::Error::Reported(_)) => (),
+                Err(err) => eprintln!("{err}"),
+            },
+            Some("q" | "quit") | None => return Ok(()),
+            Some(_) => {
+                println!("Please choose 1 or 2, or q to quit.");
+                continue;
+            }
+        }
+        println!();
+        println!("{MENU}");
+    }
+}